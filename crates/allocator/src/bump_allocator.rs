@@ -1,10 +1,15 @@
 use core::{alloc::Layout, ptr::NonNull};
 
-use crate::common::{Allocator, align_up};
+use crate::{
+    common::{AllocError, Allocator, align_up, is_aligned, move_block},
+    region_set::RegionSet,
+};
 
 pub struct BumpAllocator {
     heap_end: usize,
     next_paddr: usize,
+    // Regions used once the current one is full
+    spare_regions: RegionSet,
 }
 
 impl Allocator for BumpAllocator {
@@ -12,31 +17,88 @@ impl Allocator for BumpAllocator {
         BumpAllocator {
             heap_end: end,
             next_paddr: start,
+            spare_regions: RegionSet::new(),
         }
     }
 
-    fn allocate(&mut self, layout: Layout) -> Result<NonNull<u8>, ()> {
-        let start_paddr = align_up(self.next_paddr, layout.align());
-
-        let end_paddr = match start_paddr.checked_add(layout.size()) {
-            Some(end) => end,
-            None => return Err(()),
-        };
+    fn add_region(&mut self, start: usize, end: usize) {
+        self.spare_regions.add(start, end);
+    }
 
-        if end_paddr > self.heap_end {
-            return Err(());
-        }
+    fn allocate(&mut self, layout: Layout) -> Result<NonNull<u8>, AllocError> {
+        loop {
+            let start_paddr = align_up(self.next_paddr, layout.align());
 
-        self.next_paddr = end_paddr;
+            if let Some(end_paddr) = start_paddr.checked_add(layout.size())
+                && end_paddr <= self.heap_end
+            {
+                self.next_paddr = end_paddr;
+                return Ok(unsafe { NonNull::new_unchecked(start_paddr as *mut u8) });
+            }
 
-        Ok(unsafe { NonNull::new_unchecked(start_paddr as *mut u8) })
+            // Move on to the next region, what is left of the current one is lost
+            let next = self
+                .spare_regions
+                .iter()
+                .next()
+                .ok_or(AllocError::OutOfMemory)?;
+            self.spare_regions.remove(next.start, next.end);
+            self.next_paddr = next.start;
+            self.heap_end = next.end;
+        }
     }
 
     fn deallocate(&mut self, _ptr: NonNull<u8>, _layout: Layout) {
         // No deallocation in bump allocator
     }
 
+    /// Grows in place when `ptr` is the last allocated block and the current region has room
+    fn grow(
+        &mut self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        let start = ptr.as_ptr() as usize;
+
+        if self.is_last(start, old_layout)
+            && is_aligned(ptr, new_layout.align())
+            && let Some(end) = start.checked_add(new_layout.size())
+            && end <= self.heap_end
+        {
+            self.next_paddr = end;
+            return Ok(ptr);
+        }
+
+        move_block(self, ptr, old_layout, new_layout)
+    }
+
+    /// Shrinks in place, the freed bytes are only reused when `ptr` is the last allocated block
+    fn shrink(
+        &mut self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        if !is_aligned(ptr, new_layout.align()) {
+            return move_block(self, ptr, old_layout, new_layout);
+        }
+
+        let start = ptr.as_ptr() as usize;
+        if self.is_last(start, old_layout) {
+            self.next_paddr = start + new_layout.size();
+        }
+
+        Ok(ptr)
+    }
+
     fn get_available(&self) -> usize {
-        self.heap_end - self.next_paddr
+        self.heap_end - self.next_paddr + self.spare_regions.total_size()
+    }
+}
+
+impl BumpAllocator {
+    fn is_last(&self, start: usize, layout: Layout) -> bool {
+        start + layout.size() == self.next_paddr
     }
 }
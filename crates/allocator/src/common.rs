@@ -1,12 +1,83 @@
-use core::{alloc::Layout, ptr::NonNull};
+use core::{alloc::Layout, fmt, ptr::NonNull};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AllocError {
+    OutOfMemory,
+    /// The alignment is bigger than what the allocator can provide
+    BadAlignment,
+    Uninitialized,
+}
+
+impl fmt::Display for AllocError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AllocError::OutOfMemory => write!(f, "out of memory"),
+            AllocError::BadAlignment => write!(f, "unsupported alignment"),
+            AllocError::Uninitialized => write!(f, "allocator uninitialized"),
+        }
+    }
+}
 
 pub trait Allocator {
     fn init(start: usize, end: usize) -> Self;
-    fn allocate(&mut self, layout: Layout) -> Result<NonNull<u8>, ()>;
+    /// Gives the memory of `[start, end)` to the allocator, it must not overlap managed memory
+    fn add_region(&mut self, start: usize, end: usize);
+    fn allocate(&mut self, layout: Layout) -> Result<NonNull<u8>, AllocError>;
     fn deallocate(&mut self, _ptr: NonNull<u8>, _layout: Layout);
     fn get_available(&self) -> usize;
+
+    fn allocate_zeroed(&mut self, layout: Layout) -> Result<NonNull<u8>, AllocError> {
+        let ptr = self.allocate(layout)?;
+        unsafe { ptr.write_bytes(0, layout.size()) };
+        Ok(ptr)
+    }
+
+    /// Resizes the block at `ptr` to `new_layout`, which is not smaller than `old_layout`.
+    /// The block is left untouched on error.
+    ///
+    /// The default implementation always moves the block.
+    fn grow(
+        &mut self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        move_block(self, ptr, old_layout, new_layout)
+    }
+
+    /// Resizes the block at `ptr` to `new_layout`, which is not bigger than `old_layout`.
+    /// The block is left untouched on error.
+    ///
+    /// The default implementation always moves the block.
+    fn shrink(
+        &mut self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        move_block(self, ptr, old_layout, new_layout)
+    }
+}
+
+/// Copies the block at `ptr` in a new allocation of `new_layout` and frees the old one
+pub fn move_block<A: Allocator + ?Sized>(
+    allocator: &mut A,
+    ptr: NonNull<u8>,
+    old_layout: Layout,
+    new_layout: Layout,
+) -> Result<NonNull<u8>, AllocError> {
+    let new_ptr = allocator.allocate(new_layout)?;
+    unsafe {
+        new_ptr.copy_from_nonoverlapping(ptr, old_layout.size().min(new_layout.size()));
+    }
+    allocator.deallocate(ptr, old_layout);
+    Ok(new_ptr)
 }
 
 pub fn align_up(addr: usize, align: usize) -> usize {
     (addr + align - 1) & !(align - 1)
 }
+
+pub fn is_aligned(ptr: NonNull<u8>, align: usize) -> bool {
+    (ptr.as_ptr() as usize).is_multiple_of(align)
+}
@@ -0,0 +1,192 @@
+use core::{alloc::Layout, mem, ptr::NonNull};
+
+use crate::common::{AllocError, Allocator, align_up};
+
+/// Bytes checked before and after every block
+pub const REDZONE_SIZE: usize = 16;
+
+/// Number of freed blocks kept poisoned before going back to the inner allocator
+pub const QUARANTINE_SIZE: usize = 64;
+
+pub const ALLOCATED_PATTERN: u8 = 0xAA;
+pub const FREED_PATTERN: u8 = 0xDD;
+pub const REDZONE_PATTERN: u8 = 0xFD;
+
+const ALLOCATED_MAGIC: usize = 0xA110_CA7E_D0B1_0C00;
+const FREED_MAGIC: usize = 0xF4EE_D0B1_0C00_DEAD;
+
+/// Written right before the front redzone of every block
+#[derive(Clone, Copy)]
+struct Header {
+    magic: usize,
+    size: usize,
+    align: usize,
+}
+
+const HEADER_SIZE: usize = mem::size_of::<Header>();
+
+/// Wraps an allocator to catch heap misuse, panicking with the address and the layout of the block.
+///
+/// - Redzones before and after every block are checked when it is freed
+/// - Fresh memory is filled with `ALLOCATED_PATTERN` and freed memory with `FREED_PATTERN`
+/// - Double frees and frees with a different `Layout` than the allocation are detected
+/// - Freed blocks stay in a quarantine for a while, writes to them are detected when they leave it
+pub struct DebugAllocator<A: Allocator> {
+    inner: A,
+    quarantine: [Option<(NonNull<u8>, Layout)>; QUARANTINE_SIZE],
+    next_quarantined: usize,
+}
+
+// Quarantined blocks are only reachable through the allocator
+unsafe impl<A: Allocator + Send> Send for DebugAllocator<A> {}
+
+impl<A: Allocator> DebugAllocator<A> {
+    pub fn inner(&self) -> &A {
+        &self.inner
+    }
+
+    /// Offset of the user pointer in the inner block and layout of the inner block
+    fn inner_layout(layout: &Layout) -> Option<(usize, Layout)> {
+        let align = layout.align().max(mem::align_of::<Header>());
+        let offset = align_up(HEADER_SIZE + REDZONE_SIZE, align);
+        let size = offset
+            .checked_add(layout.size())?
+            .checked_add(REDZONE_SIZE)?;
+
+        Some((offset, Layout::from_size_align(size, align).ok()?))
+    }
+
+    fn header(ptr: NonNull<u8>) -> *mut Header {
+        (ptr.as_ptr() as usize - REDZONE_SIZE - HEADER_SIZE) as *mut Header
+    }
+
+    fn front_redzone(ptr: NonNull<u8>) -> &'static mut [u8] {
+        unsafe { core::slice::from_raw_parts_mut(ptr.as_ptr().sub(REDZONE_SIZE), REDZONE_SIZE) }
+    }
+
+    fn back_redzone(ptr: NonNull<u8>, size: usize) -> &'static mut [u8] {
+        unsafe { core::slice::from_raw_parts_mut(ptr.as_ptr().add(size), REDZONE_SIZE) }
+    }
+
+    fn contents(ptr: NonNull<u8>, size: usize) -> &'static mut [u8] {
+        unsafe { core::slice::from_raw_parts_mut(ptr.as_ptr(), size) }
+    }
+
+    /// Panics if the block at `ptr` is not a live allocation of `layout` with intact redzones
+    fn check_live(ptr: NonNull<u8>, layout: &Layout) {
+        let header = unsafe { Self::header(ptr).read() };
+
+        match header.magic {
+            ALLOCATED_MAGIC => {}
+            FREED_MAGIC => panic!("Double free of {ptr:?} ({layout:?})"),
+            _ => panic!(
+                "Free of {ptr:?} ({layout:?}) which is not allocated or has a corrupted header"
+            ),
+        }
+
+        if header.size != layout.size() || header.align != layout.align() {
+            panic!(
+                "Free of {ptr:?} with {layout:?}, it was allocated with size {} and align {}",
+                header.size, header.align
+            );
+        }
+
+        if Self::front_redzone(ptr)
+            .iter()
+            .any(|&b| b != REDZONE_PATTERN)
+        {
+            panic!("Redzone before {ptr:?} ({layout:?}) overwritten");
+        }
+        if Self::back_redzone(ptr, layout.size())
+            .iter()
+            .any(|&b| b != REDZONE_PATTERN)
+        {
+            panic!("Redzone after {ptr:?} ({layout:?}) overwritten");
+        }
+    }
+
+    /// Gives a quarantined block back to the inner allocator after checking nothing wrote to it
+    fn release(&mut self, ptr: NonNull<u8>, layout: Layout) {
+        if Self::contents(ptr, layout.size())
+            .iter()
+            .any(|&b| b != FREED_PATTERN)
+        {
+            panic!(
+                "Use after free of {ptr:?} ({layout:?}), the block was written to after its free"
+            );
+        }
+
+        // Cannot fail, the same layout was computed for the allocation
+        let (offset, inner_layout) = Self::inner_layout(&layout).unwrap();
+        let block = unsafe { NonNull::new_unchecked(ptr.as_ptr().sub(offset)) };
+        self.inner.deallocate(block, inner_layout);
+    }
+
+    /// Gives every quarantined block back to the inner allocator
+    fn flush_quarantine(&mut self) {
+        for i in 0..QUARANTINE_SIZE {
+            if let Some((ptr, layout)) = self.quarantine[i].take() {
+                self.release(ptr, layout);
+            }
+        }
+    }
+}
+
+impl<A: Allocator> Allocator for DebugAllocator<A> {
+    fn init(start: usize, end: usize) -> Self {
+        DebugAllocator {
+            inner: A::init(start, end),
+            quarantine: [None; QUARANTINE_SIZE],
+            next_quarantined: 0,
+        }
+    }
+
+    fn add_region(&mut self, start: usize, end: usize) {
+        self.inner.add_region(start, end);
+    }
+
+    fn allocate(&mut self, layout: Layout) -> Result<NonNull<u8>, AllocError> {
+        let (offset, inner_layout) = Self::inner_layout(&layout).ok_or(AllocError::OutOfMemory)?;
+
+        let block = match self.inner.allocate(inner_layout) {
+            Ok(block) => block,
+            Err(AllocError::OutOfMemory) => {
+                // Quarantined memory is better used than reported as missing
+                self.flush_quarantine();
+                self.inner.allocate(inner_layout)?
+            }
+            Err(error) => return Err(error),
+        };
+        let ptr = unsafe { NonNull::new_unchecked(block.as_ptr().add(offset)) };
+
+        unsafe {
+            Self::header(ptr).write(Header {
+                magic: ALLOCATED_MAGIC,
+                size: layout.size(),
+                align: layout.align(),
+            })
+        };
+        Self::front_redzone(ptr).fill(REDZONE_PATTERN);
+        Self::back_redzone(ptr, layout.size()).fill(REDZONE_PATTERN);
+        Self::contents(ptr, layout.size()).fill(ALLOCATED_PATTERN);
+
+        Ok(ptr)
+    }
+
+    fn deallocate(&mut self, ptr: NonNull<u8>, layout: Layout) {
+        Self::check_live(ptr, &layout);
+
+        unsafe { (*Self::header(ptr)).magic = FREED_MAGIC };
+        Self::contents(ptr, layout.size()).fill(FREED_PATTERN);
+
+        let slot = self.next_quarantined;
+        self.next_quarantined = (slot + 1) % QUARANTINE_SIZE;
+        if let Some((old_ptr, old_layout)) = self.quarantine[slot].replace((ptr, layout)) {
+            self.release(old_ptr, old_layout);
+        }
+    }
+
+    fn get_available(&self) -> usize {
+        self.inner.get_available()
+    }
+}
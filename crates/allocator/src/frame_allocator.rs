@@ -0,0 +1,285 @@
+use core::{fmt, ptr::NonNull};
+
+use crate::common::align_up;
+
+pub const FRAME_SIZE: usize = 4096;
+
+/// Number of orders, the biggest block is `FRAME_SIZE << (MAX_ORDER - 1)` bytes (4 MiB)
+pub const MAX_ORDER: usize = 11;
+
+/// Physical memory address
+#[repr(transparent)]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct PhysAddr(usize);
+
+impl PhysAddr {
+    pub const fn new(address: usize) -> PhysAddr {
+        PhysAddr(address)
+    }
+
+    pub const fn as_usize(&self) -> usize {
+        self.0
+    }
+
+    pub const fn as_ptr<T>(&self) -> *mut T {
+        self.0 as *mut T
+    }
+
+    pub const fn is_frame_aligned(&self) -> bool {
+        self.0.is_multiple_of(FRAME_SIZE)
+    }
+
+    pub const fn offset(&self, bytes: usize) -> PhysAddr {
+        PhysAddr(self.0 + bytes)
+    }
+}
+
+impl fmt::Debug for PhysAddr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "PhysAddr({:#x})", self.0)
+    }
+}
+
+impl fmt::Display for PhysAddr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:#x}", self.0)
+    }
+}
+
+/// Link written at the start of every free block
+struct FreeBlock {
+    next: Option<NonNull<FreeBlock>>,
+}
+
+/// Buddy allocator of physical frames
+///
+/// A block of order `n` is `2^n` contiguous frames aligned on its own size.
+/// Allocations split bigger blocks in two buddies, frees merge a block with its buddy when it is free.
+pub struct FrameAllocator {
+    free_lists: [Option<NonNull<FreeBlock>>; MAX_ORDER],
+    free_frames: usize,
+    total_frames: usize,
+}
+
+// The free lists are only reachable through the allocator
+unsafe impl Send for FrameAllocator {}
+
+impl FrameAllocator {
+    pub const fn new() -> FrameAllocator {
+        FrameAllocator {
+            free_lists: [None; MAX_ORDER],
+            free_frames: 0,
+            total_frames: 0,
+        }
+    }
+
+    /// Gives the frames of `[start, end)` to the allocator, partial frames at both ends are ignored
+    ///
+    /// # Safety
+    ///
+    /// The region must be unused memory that is not already managed by the allocator.
+    pub unsafe fn add_region(&mut self, start: PhysAddr, end: PhysAddr) {
+        let start = align_up(start.as_usize(), FRAME_SIZE);
+        let end = end.as_usize() & !(FRAME_SIZE - 1);
+
+        if end > start {
+            let frames = (end - start) / FRAME_SIZE;
+            self.total_frames += frames;
+            self.free_frames += frames;
+            self.free_range(start, end);
+        }
+    }
+
+    /// Allocates `2^order` contiguous frames aligned on their size
+    pub fn allocate(&mut self, order: usize) -> Option<PhysAddr> {
+        if order >= MAX_ORDER {
+            return None;
+        }
+
+        let mut current = (order..MAX_ORDER).find(|&o| self.free_lists[o].is_some())?;
+        let address = self.pop(current)?;
+
+        // Split until the block has the requested order, upper halves go back to the free lists
+        while current > order {
+            current -= 1;
+            self.push(current, address + (FRAME_SIZE << current));
+        }
+
+        self.free_frames -= 1 << order;
+        Some(PhysAddr(address))
+    }
+
+    /// Allocates `count` contiguous frames
+    ///
+    /// Frames past `count` in the underlying block are given back immediately,
+    /// the run must be freed with [`FrameAllocator::deallocate_frames`] and the same `count`.
+    pub fn allocate_frames(&mut self, count: usize) -> Option<PhysAddr> {
+        if count == 0 {
+            return None;
+        }
+
+        let order = count.next_power_of_two().trailing_zeros() as usize;
+        let address = self.allocate(order)?;
+
+        let excess = (1 << order) - count;
+        if excess > 0 {
+            let start = address.as_usize() + count * FRAME_SIZE;
+            self.free_frames += excess;
+            self.free_range(start, start + excess * FRAME_SIZE);
+        }
+
+        Some(address)
+    }
+
+    /// Frees a block returned by [`FrameAllocator::allocate`] with the same `order`
+    ///
+    /// Panics on a double free in debug builds, the free lists would be corrupted otherwise.
+    pub fn deallocate(&mut self, address: PhysAddr, order: usize) {
+        assert!(order < MAX_ORDER, "invalid order {order}");
+        assert!(
+            address.as_usize().is_multiple_of(FRAME_SIZE << order),
+            "{address} is not a block of order {order}"
+        );
+
+        let start = address.as_usize();
+        // NOTE: Walks every free list, too slow to be checked in release builds
+        debug_assert!(
+            !self.is_free(start, start + (FRAME_SIZE << order)),
+            "double free of {address}"
+        );
+
+        self.free_frames += 1 << order;
+        self.free_block(start, order);
+    }
+
+    /// Frees a run returned by [`FrameAllocator::allocate_frames`] with the same `count`
+    ///
+    /// Panics on a double free in debug builds, like [`FrameAllocator::deallocate`].
+    pub fn deallocate_frames(&mut self, address: PhysAddr, count: usize) {
+        assert!(address.is_frame_aligned(), "{address} is not frame aligned");
+
+        let start = address.as_usize();
+        let end = count
+            .checked_mul(FRAME_SIZE)
+            .and_then(|size| start.checked_add(size))
+            .unwrap_or_else(|| panic!("{count} frames at {address} overflow"));
+        debug_assert!(!self.is_free(start, end), "double free of {address}");
+
+        self.free_frames += count;
+        self.free_range(start, end);
+    }
+
+    pub fn free_frames(&self) -> usize {
+        self.free_frames
+    }
+
+    pub fn total_frames(&self) -> usize {
+        self.total_frames
+    }
+
+    /// Number of free blocks for each order
+    pub fn free_blocks(&self) -> [usize; MAX_ORDER] {
+        let mut counts = [0; MAX_ORDER];
+        for (order, count) in counts.iter_mut().enumerate() {
+            *count = self.blocks(order).count();
+        }
+        counts
+    }
+
+    //
+    // NON-PUBLIC INTERFACE
+    //
+
+    /// Addresses of the free blocks of `order`
+    fn blocks(&self, order: usize) -> impl Iterator<Item = usize> + use<'_> {
+        let mut current = self.free_lists[order];
+        core::iter::from_fn(move || {
+            let block = current?;
+            current = unsafe { block.as_ref().next };
+            Some(block.as_ptr() as usize)
+        })
+    }
+
+    /// Whether a free block overlaps `[start, end)`
+    fn is_free(&self, start: usize, end: usize) -> bool {
+        (0..MAX_ORDER).any(|order| {
+            self.blocks(order)
+                .any(|block| block < end && start < block + (FRAME_SIZE << order))
+        })
+    }
+
+    /// Frees `[start, end)` as the biggest aligned blocks that fit
+    fn free_range(&mut self, start: usize, end: usize) {
+        let mut address = start;
+        while address < end {
+            let alignment_order = (address.trailing_zeros() as usize).saturating_sub(12);
+            let mut order = alignment_order.min(MAX_ORDER - 1);
+            while address + (FRAME_SIZE << order) > end {
+                order -= 1;
+            }
+
+            self.free_block(address, order);
+            address += FRAME_SIZE << order;
+        }
+    }
+
+    /// Puts a block back in the free lists, merging it with its buddy as long as possible
+    fn free_block(&mut self, mut address: usize, mut order: usize) {
+        while order < MAX_ORDER - 1 {
+            let buddy = address ^ (FRAME_SIZE << order);
+            if !self.remove(order, buddy) {
+                break;
+            }
+
+            address = address.min(buddy);
+            order += 1;
+        }
+
+        self.push(order, address);
+    }
+
+    fn push(&mut self, order: usize, address: usize) {
+        let block = address as *mut FreeBlock;
+        unsafe {
+            block.write(FreeBlock {
+                next: self.free_lists[order],
+            });
+            self.free_lists[order] = Some(NonNull::new_unchecked(block));
+        }
+    }
+
+    fn pop(&mut self, order: usize) -> Option<usize> {
+        let block = self.free_lists[order]?;
+        self.free_lists[order] = unsafe { block.as_ref().next };
+        Some(block.as_ptr() as usize)
+    }
+
+    /// Removes the block at `address` from the free list of `order`, returns `false` if it is not there
+    fn remove(&mut self, order: usize, address: usize) -> bool {
+        let mut prev: Option<NonNull<FreeBlock>> = None;
+        let mut current = self.free_lists[order];
+
+        while let Some(block) = current {
+            let next = unsafe { block.as_ref().next };
+
+            if block.as_ptr() as usize == address {
+                match prev {
+                    Some(mut prev) => unsafe { prev.as_mut().next = next },
+                    None => self.free_lists[order] = next,
+                }
+                return true;
+            }
+
+            prev = Some(block);
+            current = next;
+        }
+
+        false
+    }
+}
+
+impl Default for FrameAllocator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
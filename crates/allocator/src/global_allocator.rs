@@ -5,23 +5,201 @@ use core::{
 
 use spin::Mutex;
 
-use crate::common::Allocator;
+#[cfg(feature = "debug-heap")]
+use crate::debug_allocator::DebugAllocator;
+use crate::{
+    common::{AllocError, Allocator},
+    region_set::RegionSet,
+    slab_allocator::{CacheStats, FramePool, SIZE_CLASSES, SlabAllocator},
+    stats::{AllocStats, AllocationTracker, LiveAllocation},
+};
 
 static UNINIT_MSG: &str = "Allocator uninitialized";
 
-pub struct GlobalAllocator<A: Allocator>(Mutex<Option<A>>);
+/// Global allocator wrapper
+///
+/// The lock is only taken inside a critical section so that an interrupt handler
+/// can never spin on a lock held by the code it interrupted.
+/// The final binary must provide a `critical-section` implementation.
+///
+/// Statistics and the tracking of live allocations are disabled until enabled with
+/// [`GlobalAllocator::enable_stats`] and [`GlobalAllocator::enable_tracking`].
+pub struct GlobalAllocator<A: Allocator>(Mutex<State<A>>);
+
+struct State<A: Allocator> {
+    allocator: Option<A>,
+    stats: Option<AllocStats>,
+    // NOTE: The tracker is too big to be built on the stack when tracking is enabled, it lives in
+    // the allocator from the start
+    tracker: AllocationTracker,
+    tracking: bool,
+}
+
+impl<A: Allocator> State<A> {
+    fn allocator(&mut self) -> Result<&mut A, AllocError> {
+        self.allocator.as_mut().ok_or(AllocError::Uninitialized)
+    }
+
+    fn tracker(&mut self) -> Option<&mut AllocationTracker> {
+        self.tracking.then_some(&mut self.tracker)
+    }
+
+    fn record_allocation(&mut self, layout: Layout, result: Result<NonNull<u8>, AllocError>) {
+        match result {
+            Ok(ptr) => {
+                if let Some(stats) = &mut self.stats {
+                    stats.record_allocation(layout.size());
+                }
+                if let Some(tracker) = self.tracker() {
+                    tracker.insert(ptr.as_ptr() as usize, layout);
+                }
+            }
+            Err(_) => self.record_failure(),
+        }
+    }
+
+    fn record_free(&mut self, ptr: NonNull<u8>, layout: Layout) {
+        if let Some(stats) = &mut self.stats {
+            stats.record_free(layout.size());
+        }
+        if let Some(tracker) = self.tracker() {
+            tracker.remove(ptr.as_ptr() as usize);
+        }
+    }
+
+    fn record_reallocation(
+        &mut self,
+        ptr: NonNull<u8>,
+        layout: Layout,
+        new_layout: Layout,
+        result: Result<NonNull<u8>, AllocError>,
+    ) {
+        match result {
+            Ok(new_ptr) => {
+                if let Some(stats) = &mut self.stats {
+                    stats.record_reallocation(layout.size(), new_layout.size());
+                }
+                if let Some(tracker) = self.tracker() {
+                    tracker.moved(ptr.as_ptr() as usize, new_ptr.as_ptr() as usize, new_layout);
+                }
+            }
+            Err(_) => self.record_failure(),
+        }
+    }
+
+    fn record_failure(&mut self) {
+        if let Some(stats) = &mut self.stats {
+            stats.record_failure();
+        }
+    }
+}
 
 impl<A: Allocator> GlobalAllocator<A> {
     pub const fn new() -> GlobalAllocator<A> {
-        GlobalAllocator(Mutex::new(None))
+        GlobalAllocator(Mutex::new(State {
+            allocator: None,
+            stats: None,
+            tracker: AllocationTracker::new(),
+            tracking: false,
+        }))
     }
 
-    pub fn init(&self, start: usize, size: usize) {
-        *self.0.lock() = Some(A::init(start, size));
+    /// Initializes the allocator with every region of `regions`
+    pub fn init(&self, regions: &RegionSet) {
+        let mut regions = regions.iter();
+        let first = regions.next().expect("No memory region for the allocator");
+
+        let mut allocator = A::init(first.start, first.end);
+        for region in regions {
+            allocator.add_region(region.start, region.end);
+        }
+
+        critical_section::with(|_| self.0.lock().allocator = Some(allocator));
     }
 
     pub fn get_available(&self) -> usize {
-        self.0.lock().as_ref().expect(UNINIT_MSG).get_available()
+        self.with_state(|state| state.allocator().expect(UNINIT_MSG).get_available())
+    }
+
+    /// Starts counting allocations, frees and failures, does nothing if already enabled.
+    /// Blocks allocated before are not counted in the bytes in use.
+    pub fn enable_stats(&self) {
+        self.with_state(|state| {
+            state.stats.get_or_insert_with(AllocStats::new);
+        });
+    }
+
+    /// `None` if statistics are disabled
+    pub fn stats(&self) -> Option<AllocStats> {
+        self.with_state(|state| state.stats)
+    }
+
+    /// Starts recording every live allocation with its `Layout`, does nothing if already enabled.
+    ///
+    /// Every allocation and free then goes through a hash table of up to
+    /// [`TRACKED_ALLOCATIONS`](crate::TRACKED_ALLOCATIONS) entries.
+    pub fn enable_tracking(&self) {
+        self.with_state(|state| state.tracking = true);
+    }
+
+    /// Marker to give to [`GlobalAllocator::for_each_outstanding`], `None` if tracking is disabled
+    pub fn marker(&self) -> Option<u64> {
+        self.with_state(|state| state.tracker().map(|tracker| tracker.marker()))
+    }
+
+    /// Allocations that the tracker could not record because it was full
+    pub fn untracked_allocations(&self) -> usize {
+        self.with_state(|state| state.tracker().map_or(0, |tracker| tracker.untracked()))
+    }
+
+    /// Calls `f` with every recorded allocation made since `marker` that is still live, oldest
+    /// first
+    ///
+    /// `f` runs outside of the allocator lock so it can allocate, allocations it makes are not
+    /// reported.
+    pub fn for_each_outstanding(&self, marker: u64, mut f: impl FnMut(LiveAllocation)) {
+        let Some(end) = self.marker() else {
+            return;
+        };
+
+        let mut from = marker;
+        while let Some(allocation) = self.with_state(|state| {
+            state
+                .tracker()
+                .and_then(|tracker| tracker.first_between(from, end))
+        }) {
+            f(allocation);
+            from = allocation.id + 1;
+        }
+    }
+
+    /// Releases the lock, to be used by the panic handler when a panic may come from inside the allocator
+    ///
+    /// # Safety
+    ///
+    /// Nothing must be using the allocator, the code holding the lock must never resume.
+    pub unsafe fn force_unlock(&self) {
+        if self.0.is_locked() {
+            unsafe { self.0.force_unlock() };
+        }
+    }
+
+    /// Runs `f` on the state inside a critical section
+    fn with_state<R>(&self, f: impl FnOnce(&mut State<A>) -> R) -> R {
+        critical_section::with(|_| f(&mut self.0.lock()))
+    }
+}
+
+impl<P: FramePool> GlobalAllocator<SlabAllocator<P>> {
+    pub fn cache_stats(&self) -> [CacheStats; SIZE_CLASSES.len()] {
+        self.with_state(|state| state.allocator().expect(UNINIT_MSG).cache_stats())
+    }
+}
+
+#[cfg(feature = "debug-heap")]
+impl<P: FramePool> GlobalAllocator<DebugAllocator<SlabAllocator<P>>> {
+    pub fn cache_stats(&self) -> [CacheStats; SIZE_CLASSES.len()] {
+        self.with_state(|state| state.allocator().expect(UNINIT_MSG).inner().cache_stats())
     }
 }
 
@@ -33,20 +211,48 @@ impl<A: Allocator> Default for GlobalAllocator<A> {
 
 unsafe impl<A: Allocator> GlobalAlloc for GlobalAllocator<A> {
     unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
-        self.0
-            .lock()
-            .as_mut()
-            .expect(UNINIT_MSG)
-            .allocate(layout)
-            .ok()
-            .map_or(core::ptr::null_mut(), |a| a.as_ptr())
+        self.with_state(|state| {
+            let result = state.allocator().and_then(|a| a.allocate(layout));
+            state.record_allocation(layout, result);
+            result
+        })
+        .map_or(core::ptr::null_mut(), |a| a.as_ptr())
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        self.with_state(|state| {
+            let result = state.allocator().and_then(|a| a.allocate_zeroed(layout));
+            state.record_allocation(layout, result);
+            result
+        })
+        .map_or(core::ptr::null_mut(), |a| a.as_ptr())
     }
 
     unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
-        self.0
-            .lock()
-            .as_mut()
-            .expect(UNINIT_MSG)
-            .deallocate(unsafe { NonNull::new_unchecked(ptr) }, layout)
+        let ptr = unsafe { NonNull::new_unchecked(ptr) };
+
+        self.with_state(|state| {
+            state.allocator().expect(UNINIT_MSG).deallocate(ptr, layout);
+            state.record_free(ptr, layout);
+        })
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        // NOTE: `GlobalAlloc` guarantees that `new_size` rounded up to `layout.align()` does not overflow
+        let new_layout = unsafe { Layout::from_size_align_unchecked(new_size, layout.align()) };
+        let ptr = unsafe { NonNull::new_unchecked(ptr) };
+
+        self.with_state(|state| {
+            let result = state.allocator().and_then(|a| {
+                if new_size >= layout.size() {
+                    a.grow(ptr, layout, new_layout)
+                } else {
+                    a.shrink(ptr, layout, new_layout)
+                }
+            });
+            state.record_reallocation(ptr, layout, new_layout, result);
+            result
+        })
+        .map_or(core::ptr::null_mut(), |a| a.as_ptr())
     }
 }
@@ -2,7 +2,25 @@
 
 mod bump_allocator;
 mod common;
+#[cfg(feature = "debug-heap")]
+mod debug_allocator;
+mod frame_allocator;
 mod global_allocator;
+mod linked_list_allocator;
+mod region_set;
+mod slab_allocator;
+mod stats;
 
 pub use bump_allocator::BumpAllocator;
+pub use common::{AllocError, Allocator};
+#[cfg(feature = "debug-heap")]
+pub use debug_allocator::{
+    ALLOCATED_PATTERN, DebugAllocator, FREED_PATTERN, QUARANTINE_SIZE, REDZONE_PATTERN,
+    REDZONE_SIZE,
+};
+pub use frame_allocator::{FRAME_SIZE, FrameAllocator, MAX_ORDER, PhysAddr};
 pub use global_allocator::GlobalAllocator;
+pub use linked_list_allocator::LinkedListAllocator;
+pub use region_set::{MAX_REGIONS, Region, RegionSet};
+pub use slab_allocator::{CacheStats, FramePool, SIZE_CLASSES, SlabAllocator};
+pub use stats::{AllocStats, HISTOGRAM_BUCKETS, LiveAllocation, TRACKED_ALLOCATIONS};
@@ -0,0 +1,245 @@
+use core::{alloc::Layout, mem, ptr::NonNull};
+
+use crate::common::{AllocError, Allocator, align_up, is_aligned, move_block};
+
+/// Header written at the start of every free block
+struct FreeBlock {
+    size: usize,
+    next: Option<NonNull<FreeBlock>>,
+}
+
+const MIN_BLOCK_SIZE: usize = mem::size_of::<FreeBlock>();
+const BLOCK_ALIGN: usize = mem::align_of::<FreeBlock>();
+
+/// First-fit allocator keeping free blocks in a list sorted by address.
+/// Adjacent free blocks are merged on deallocation.
+pub struct LinkedListAllocator {
+    head: Option<NonNull<FreeBlock>>,
+    available: usize,
+}
+
+// The free list is only reachable through the allocator, which is behind a lock
+unsafe impl Send for LinkedListAllocator {}
+
+impl LinkedListAllocator {
+    /// Size actually reserved for `layout`, every block must be able to hold a `FreeBlock` once freed
+    fn block_size(layout: &Layout) -> usize {
+        align_up(layout.size().max(MIN_BLOCK_SIZE), BLOCK_ALIGN)
+    }
+
+    /// Finds where an allocation of `size` bytes aligned to `align` fits in `[block_start, block_end)`.
+    /// Leftovers on both sides must be big enough to become free blocks.
+    fn fit(
+        block_start: usize,
+        block_end: usize,
+        size: usize,
+        align: usize,
+    ) -> Option<(usize, usize)> {
+        let mut start = align_up(block_start, align);
+        if start != block_start && start - block_start < MIN_BLOCK_SIZE {
+            start = align_up(block_start + MIN_BLOCK_SIZE, align);
+        }
+
+        let end = start.checked_add(size)?;
+        if end > block_end {
+            return None;
+        }
+
+        let remainder = block_end - end;
+        if remainder != 0 && remainder < MIN_BLOCK_SIZE {
+            return None;
+        }
+
+        Some((start, end))
+    }
+
+    /// Takes `size` bytes at `address` out of the free block starting exactly there.
+    /// Returns `false` if there is no such block or if what would be left is too small to stay free.
+    fn take_at(&mut self, address: usize, size: usize) -> bool {
+        let mut prev: Option<NonNull<FreeBlock>> = None;
+        let mut curr = self.head;
+        while let Some(block) = curr
+            && (block.as_ptr() as usize) < address
+        {
+            prev = curr;
+            curr = unsafe { block.as_ref().next };
+        }
+
+        let Some(block) = curr.filter(|b| b.as_ptr() as usize == address) else {
+            return false;
+        };
+        let FreeBlock {
+            size: block_size,
+            next,
+        } = unsafe { block.read() };
+
+        if block_size < size || (block_size > size && block_size - size < MIN_BLOCK_SIZE) {
+            return false;
+        }
+
+        match prev {
+            Some(mut prev) => unsafe { prev.as_mut().next = next },
+            None => self.head = next,
+        }
+        self.available -= block_size;
+
+        if block_size > size {
+            unsafe { self.insert_free(address + size, block_size - size) };
+        }
+
+        true
+    }
+
+    /// Adds `[address, address + size)` to the free list, merging it with its neighbours
+    ///
+    /// # Safety
+    ///
+    /// The region must be unused and not already in the free list.
+    unsafe fn insert_free(&mut self, address: usize, size: usize) {
+        self.available += size;
+
+        let mut prev: Option<NonNull<FreeBlock>> = None;
+        let mut curr = self.head;
+        while let Some(block) = curr
+            && (block.as_ptr() as usize) < address
+        {
+            prev = curr;
+            curr = unsafe { block.as_ref().next };
+        }
+
+        let mut new_block = unsafe { NonNull::new_unchecked(address as *mut FreeBlock) };
+        unsafe { new_block.write(FreeBlock { size, next: curr }) };
+
+        unsafe {
+            if let Some(next) = curr
+                && address + size == next.as_ptr() as usize
+            {
+                let new_block = new_block.as_mut();
+                new_block.size += next.as_ref().size;
+                new_block.next = next.as_ref().next;
+            }
+
+            match prev {
+                Some(mut prev) if prev.as_ptr() as usize + prev.as_ref().size == address => {
+                    let prev = prev.as_mut();
+                    prev.size += new_block.as_ref().size;
+                    prev.next = new_block.as_ref().next;
+                }
+                Some(mut prev) => prev.as_mut().next = Some(new_block),
+                None => self.head = Some(new_block),
+            }
+        }
+    }
+}
+
+impl Allocator for LinkedListAllocator {
+    fn init(start: usize, end: usize) -> Self {
+        let mut allocator = LinkedListAllocator {
+            head: None,
+            available: 0,
+        };
+        allocator.add_region(start, end);
+        allocator
+    }
+
+    fn add_region(&mut self, start: usize, end: usize) {
+        let start = align_up(start, BLOCK_ALIGN);
+        let end = end & !(BLOCK_ALIGN - 1);
+        if end > start && end - start >= MIN_BLOCK_SIZE {
+            unsafe { self.insert_free(start, end - start) };
+        }
+    }
+
+    fn allocate(&mut self, layout: Layout) -> Result<NonNull<u8>, AllocError> {
+        let size = Self::block_size(&layout);
+        let align = layout.align().max(BLOCK_ALIGN);
+
+        let mut prev: Option<NonNull<FreeBlock>> = None;
+        let mut curr = self.head;
+
+        while let Some(block) = curr {
+            let block_start = block.as_ptr() as usize;
+            let FreeBlock {
+                size: block_size,
+                next,
+            } = unsafe { block.read() };
+            let block_end = block_start + block_size;
+
+            if let Some((start, end)) = Self::fit(block_start, block_end, size, align) {
+                // Unlink the block, then give back what is left on each side
+                match prev {
+                    Some(mut prev) => unsafe { prev.as_mut().next = next },
+                    None => self.head = next,
+                }
+                self.available -= block_size;
+
+                unsafe {
+                    if start > block_start {
+                        self.insert_free(block_start, start - block_start);
+                    }
+                    if end < block_end {
+                        self.insert_free(end, block_end - end);
+                    }
+
+                    return Ok(NonNull::new_unchecked(start as *mut u8));
+                }
+            }
+
+            prev = curr;
+            curr = next;
+        }
+
+        Err(AllocError::OutOfMemory)
+    }
+
+    fn deallocate(&mut self, ptr: NonNull<u8>, layout: Layout) {
+        unsafe { self.insert_free(ptr.as_ptr() as usize, Self::block_size(&layout)) };
+    }
+
+    /// Grows in place by taking the start of the free block right after `ptr` when there is one
+    fn grow(
+        &mut self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        let old_size = Self::block_size(&old_layout);
+        let new_size = Self::block_size(&new_layout);
+
+        if is_aligned(ptr, new_layout.align())
+            && (new_size == old_size
+                || self.take_at(ptr.as_ptr() as usize + old_size, new_size - old_size))
+        {
+            return Ok(ptr);
+        }
+
+        move_block(self, ptr, old_layout, new_layout)
+    }
+
+    /// Shrinks in place when the freed tail is big enough to become a free block
+    fn shrink(
+        &mut self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        let old_size = Self::block_size(&old_layout);
+        let new_size = Self::block_size(&new_layout);
+
+        if is_aligned(ptr, new_layout.align()) {
+            if new_size == old_size {
+                return Ok(ptr);
+            }
+            if old_size - new_size >= MIN_BLOCK_SIZE {
+                unsafe { self.insert_free(ptr.as_ptr() as usize + new_size, old_size - new_size) };
+                return Ok(ptr);
+            }
+        }
+
+        move_block(self, ptr, old_layout, new_layout)
+    }
+
+    fn get_available(&self) -> usize {
+        self.available
+    }
+}
@@ -0,0 +1,164 @@
+use core::fmt;
+
+pub const MAX_REGIONS: usize = 32;
+
+/// Memory region `[start, end)`
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+pub struct Region {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Region {
+    pub const fn new(start: usize, end: usize) -> Region {
+        Region { start, end }
+    }
+
+    pub const fn size(&self) -> usize {
+        self.end - self.start
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.end <= self.start
+    }
+}
+
+impl fmt::Debug for Region {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:#x}..{:#x}", self.start, self.end)
+    }
+}
+
+/// Sorted set of disjoint memory regions, usable before the heap exists
+///
+/// **Panics if more than `MAX_REGIONS` disjoint regions are needed.**
+#[derive(Clone, Copy)]
+pub struct RegionSet {
+    regions: [Region; MAX_REGIONS],
+    len: usize,
+}
+
+impl RegionSet {
+    pub const fn new() -> RegionSet {
+        RegionSet {
+            regions: [Region::new(0, 0); MAX_REGIONS],
+            len: 0,
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Region> {
+        self.regions[..self.len].iter().copied()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn total_size(&self) -> usize {
+        self.iter().map(|r| r.size()).sum()
+    }
+
+    /// Adds `[start, end)` to the set, merging it with the regions it overlaps or touches
+    pub fn add(&mut self, start: usize, end: usize) {
+        if end <= start {
+            return;
+        }
+
+        let mut merged = Region::new(start, end);
+
+        // Absorb every region that overlaps or touches the new one
+        let mut i = 0;
+        while i < self.len {
+            let region = self.regions[i];
+            if region.start <= merged.end && merged.start <= region.end {
+                merged.start = merged.start.min(region.start);
+                merged.end = merged.end.max(region.end);
+                self.remove_at(i);
+            } else {
+                i += 1;
+            }
+        }
+
+        let index = self
+            .iter()
+            .position(|r| r.start > merged.start)
+            .unwrap_or(self.len);
+        self.insert_at(index, merged);
+    }
+
+    /// Removes `[start, end)` from the set, splitting the regions it falls in
+    pub fn remove(&mut self, start: usize, end: usize) {
+        if end <= start {
+            return;
+        }
+
+        let mut i = 0;
+        while i < self.len {
+            let region = self.regions[i];
+            if region.end <= start || end <= region.start {
+                i += 1;
+                continue;
+            }
+
+            let before = Region::new(region.start, start);
+            let after = Region::new(end, region.end);
+
+            self.remove_at(i);
+            if !after.is_empty() {
+                self.insert_at(i, after);
+            }
+            if !before.is_empty() {
+                self.insert_at(i, before);
+                i += 1;
+            }
+            if !after.is_empty() {
+                i += 1;
+            }
+        }
+    }
+
+    /// Moves the first `size` bytes (lowest addresses first) of the set into a new set
+    pub fn split_off(&mut self, size: usize) -> RegionSet {
+        let mut taken = RegionSet::new();
+        let mut remaining = size;
+
+        while remaining > 0
+            && let Some(&first) = self.regions[..self.len].first()
+        {
+            let end = first.start + first.size().min(remaining);
+            taken.add(first.start, end);
+            self.remove(first.start, end);
+            remaining -= end - first.start;
+        }
+
+        taken
+    }
+
+    fn insert_at(&mut self, index: usize, region: Region) {
+        assert!(
+            self.len < MAX_REGIONS,
+            "More than {MAX_REGIONS} memory regions"
+        );
+
+        self.regions.copy_within(index..self.len, index + 1);
+        self.regions[index] = region;
+        self.len += 1;
+    }
+
+    fn remove_at(&mut self, index: usize) {
+        self.regions.copy_within(index + 1..self.len, index);
+        self.len -= 1;
+    }
+}
+
+impl Default for RegionSet {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for RegionSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
@@ -0,0 +1,280 @@
+use core::{alloc::Layout, marker::PhantomData, ptr::NonNull};
+
+use crate::{
+    common::{AllocError, Allocator, is_aligned, move_block},
+    frame_allocator::{FRAME_SIZE, MAX_ORDER, PhysAddr},
+};
+
+/// Object sizes of the caches, bigger layouts are served by the page level
+pub const SIZE_CLASSES: [usize; 8] = [16, 32, 64, 128, 256, 512, 1024, 2048];
+
+const SLAB_SIZE: usize = FRAME_SIZE;
+
+/// Biggest alignment of the page level, the size of its biggest block
+const MAX_ALIGN: usize = FRAME_SIZE << (MAX_ORDER - 1);
+
+/// Page level of a [`SlabAllocator`], shared with the rest of the system so that the heap and the
+/// frames are a single pool of memory.
+///
+/// Implemented by a type standing for a global frame allocator, whose lock must be taken in a
+/// way that cannot deadlock with the lock of the heap.
+pub trait FramePool {
+    /// Gives the frames of `[start, end)` to the pool
+    ///
+    /// # Safety
+    ///
+    /// The memory must be unused and not already managed by the pool.
+    unsafe fn add_region(start: PhysAddr, end: PhysAddr);
+    /// `count` contiguous frames, like `FrameAllocator::allocate_frames`
+    fn allocate_frames(count: usize) -> Option<PhysAddr>;
+    fn deallocate_frames(address: PhysAddr, count: usize);
+    fn free_frames() -> usize;
+}
+
+/// Link written at the start of every free object
+struct FreeObject {
+    next: Option<NonNull<FreeObject>>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub object_size: usize,
+    pub objects_in_use: usize,
+    pub objects_free: usize,
+    pub slabs: usize,
+    /// Bytes lost to rounding requests up to `object_size` and to slab space too small for an object
+    pub waste: usize,
+}
+
+/// Cache of same-sized objects carved from whole pages
+struct ObjectCache {
+    object_size: usize,
+    free_list: Option<NonNull<FreeObject>>,
+    objects_in_use: usize,
+    objects_free: usize,
+    slabs: usize,
+    // Sum of the sizes requested by the objects in use
+    requested_bytes: usize,
+}
+
+impl ObjectCache {
+    const fn new(object_size: usize) -> ObjectCache {
+        ObjectCache {
+            object_size,
+            free_list: None,
+            objects_in_use: 0,
+            objects_free: 0,
+            slabs: 0,
+            requested_bytes: 0,
+        }
+    }
+
+    fn allocate<P: FramePool>(&mut self, size: usize) -> Option<NonNull<u8>> {
+        if self.free_list.is_none() {
+            self.grow::<P>()?;
+        }
+
+        let object = self.free_list?;
+        self.free_list = unsafe { object.as_ref().next };
+
+        self.objects_free -= 1;
+        self.objects_in_use += 1;
+        self.requested_bytes += size;
+
+        Some(object.cast())
+    }
+
+    fn deallocate(&mut self, ptr: NonNull<u8>, size: usize) {
+        self.push(ptr.cast());
+
+        self.objects_free += 1;
+        self.objects_in_use -= 1;
+        self.requested_bytes -= size;
+    }
+
+    /// Accounts for an object in use changing from `old_size` to `new_size` requested bytes
+    fn resize(&mut self, old_size: usize, new_size: usize) {
+        self.requested_bytes = self.requested_bytes - old_size + new_size;
+    }
+
+    /// Takes a new slab from the page level and splits it in free objects
+    fn grow<P: FramePool>(&mut self) -> Option<()> {
+        let slab = P::allocate_frames(SLAB_SIZE / FRAME_SIZE)?.as_usize();
+
+        let count = SLAB_SIZE / self.object_size;
+        for i in (0..count).rev() {
+            let object = (slab + i * self.object_size) as *mut FreeObject;
+            self.push(unsafe { NonNull::new_unchecked(object) });
+        }
+
+        self.slabs += 1;
+        self.objects_free += count;
+        Some(())
+    }
+
+    fn push(&mut self, object: NonNull<FreeObject>) {
+        unsafe {
+            object.write(FreeObject {
+                next: self.free_list,
+            })
+        };
+        self.free_list = Some(object);
+    }
+
+    fn stats(&self) -> CacheStats {
+        CacheStats {
+            object_size: self.object_size,
+            objects_in_use: self.objects_in_use,
+            objects_free: self.objects_free,
+            slabs: self.slabs,
+            waste: self.objects_in_use * self.object_size - self.requested_bytes
+                + self.slabs * (SLAB_SIZE % self.object_size),
+        }
+    }
+}
+
+/// Routes small layouts to per size class object caches and big ones to the frames of `P`.
+///
+/// The regions given to the allocator go to `P`. Slabs are kept by their cache once allocated,
+/// they are never given back to the page level.
+pub struct SlabAllocator<P: FramePool> {
+    caches: [ObjectCache; SIZE_CLASSES.len()],
+    pool: PhantomData<P>,
+}
+
+// Free lists are only reachable through the allocator, which is behind a lock
+unsafe impl<P: FramePool> Send for SlabAllocator<P> {}
+
+impl<P: FramePool> SlabAllocator<P> {
+    pub fn cache_stats(&self) -> [CacheStats; SIZE_CLASSES.len()] {
+        self.caches.each_ref().map(ObjectCache::stats)
+    }
+
+    /// Index of the cache serving `layout`, `None` if it must go to the page level.
+    /// Objects are aligned on their size since slabs are page aligned.
+    fn cache_index(layout: &Layout) -> Option<usize> {
+        let size = layout.size().max(layout.align());
+        SIZE_CLASSES.iter().position(|&class| class >= size)
+    }
+
+    /// Resizes the block at `ptr` without moving it, `None` if it has to move
+    fn resize_in_place(
+        &mut self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Option<NonNull<u8>> {
+        if !is_aligned(ptr, new_layout.align()) {
+            return None;
+        }
+
+        match (
+            Self::cache_index(&old_layout),
+            Self::cache_index(&new_layout),
+        ) {
+            (Some(old), Some(new)) if old == new => {
+                self.caches[old].resize(old_layout.size(), new_layout.size());
+                Some(ptr)
+            }
+            (None, None) => {
+                let old_frames = Self::page_frames(&old_layout);
+                let new_frames = Self::page_frames(&new_layout);
+
+                if new_frames > old_frames {
+                    return None;
+                }
+                if new_frames < old_frames {
+                    let tail = PhysAddr::new(ptr.as_ptr() as usize + new_frames * FRAME_SIZE);
+                    P::deallocate_frames(tail, old_frames - new_frames);
+                }
+                Some(ptr)
+            }
+            _ => None,
+        }
+    }
+
+    /// Number of frames of the page level run serving `layout`.
+    /// Runs are aligned on their size rounded up to a power of two.
+    fn page_frames(layout: &Layout) -> usize {
+        let frames = layout.size().div_ceil(FRAME_SIZE).max(1);
+
+        if layout.align() > FRAME_SIZE {
+            frames.max(layout.align() / FRAME_SIZE).next_power_of_two()
+        } else {
+            frames
+        }
+    }
+}
+
+impl<P: FramePool> Allocator for SlabAllocator<P> {
+    fn init(start: usize, end: usize) -> Self {
+        unsafe { P::add_region(PhysAddr::new(start), PhysAddr::new(end)) };
+
+        SlabAllocator {
+            caches: SIZE_CLASSES.map(ObjectCache::new),
+            pool: PhantomData,
+        }
+    }
+
+    fn add_region(&mut self, start: usize, end: usize) {
+        unsafe { P::add_region(PhysAddr::new(start), PhysAddr::new(end)) };
+    }
+
+    fn allocate(&mut self, layout: Layout) -> Result<NonNull<u8>, AllocError> {
+        if layout.align() > MAX_ALIGN {
+            return Err(AllocError::BadAlignment);
+        }
+
+        match Self::cache_index(&layout) {
+            Some(index) => self.caches[index]
+                .allocate::<P>(layout.size())
+                .ok_or(AllocError::OutOfMemory),
+            None => P::allocate_frames(Self::page_frames(&layout))
+                .map(|address| unsafe { NonNull::new_unchecked(address.as_ptr()) })
+                .ok_or(AllocError::OutOfMemory),
+        }
+    }
+
+    fn deallocate(&mut self, ptr: NonNull<u8>, layout: Layout) {
+        match Self::cache_index(&layout) {
+            Some(index) => self.caches[index].deallocate(ptr, layout.size()),
+            None => P::deallocate_frames(
+                PhysAddr::new(ptr.as_ptr() as usize),
+                Self::page_frames(&layout),
+            ),
+        }
+    }
+
+    /// Grows in place when the block already has room for `new_layout`
+    fn grow(
+        &mut self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        self.resize_in_place(ptr, old_layout, new_layout)
+            .map_or_else(|| move_block(self, ptr, old_layout, new_layout), Ok)
+    }
+
+    /// Shrinks in place when the block stays in the same cache,
+    /// page level runs give their extra frames back
+    fn shrink(
+        &mut self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        self.resize_in_place(ptr, old_layout, new_layout)
+            .map_or_else(|| move_block(self, ptr, old_layout, new_layout), Ok)
+    }
+
+    fn get_available(&self) -> usize {
+        let cached: usize = self
+            .caches
+            .iter()
+            .map(|c| c.objects_free * c.object_size)
+            .sum();
+
+        P::free_frames() * FRAME_SIZE + cached
+    }
+}
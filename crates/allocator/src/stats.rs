@@ -0,0 +1,198 @@
+use core::alloc::Layout;
+
+/// Number of buckets of the size histogram
+pub const HISTOGRAM_BUCKETS: usize = 16;
+
+/// Number of live allocations the tracker can record, the others are only counted
+pub const TRACKED_ALLOCATIONS: usize = TABLE_SIZE / 4 * 3;
+
+// Slots of the hash table of the tracker, a quarter is kept empty so probes stay short
+const TABLE_SIZE: usize = 1024;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AllocStats {
+    pub allocations: usize,
+    pub frees: usize,
+    pub reallocations: usize,
+    pub failures: usize,
+    pub bytes_in_use: usize,
+    /// Highest `bytes_in_use` seen
+    pub peak_bytes: usize,
+    /// Bucket `i` counts the requested sizes in `(2^(i-1), 2^i]`, the last bucket also holds bigger sizes
+    pub size_histogram: [usize; HISTOGRAM_BUCKETS],
+}
+
+impl AllocStats {
+    pub const fn new() -> AllocStats {
+        AllocStats {
+            allocations: 0,
+            frees: 0,
+            reallocations: 0,
+            failures: 0,
+            bytes_in_use: 0,
+            peak_bytes: 0,
+            size_histogram: [0; HISTOGRAM_BUCKETS],
+        }
+    }
+
+    /// Biggest size counted by `bucket`, `None` for the last one which has no limit
+    pub fn bucket_limit(bucket: usize) -> Option<usize> {
+        (bucket < HISTOGRAM_BUCKETS - 1).then(|| 1 << bucket)
+    }
+
+    pub(crate) fn record_allocation(&mut self, size: usize) {
+        let bucket = size.next_power_of_two().trailing_zeros() as usize;
+        self.size_histogram[bucket.min(HISTOGRAM_BUCKETS - 1)] += 1;
+
+        self.allocations += 1;
+        self.add_bytes(size);
+    }
+
+    pub(crate) fn record_free(&mut self, size: usize) {
+        self.frees += 1;
+        // Saturating since the block may predate the statistics
+        self.bytes_in_use = self.bytes_in_use.saturating_sub(size);
+    }
+
+    pub(crate) fn record_reallocation(&mut self, old_size: usize, new_size: usize) {
+        self.reallocations += 1;
+        self.bytes_in_use = self.bytes_in_use.saturating_sub(old_size);
+        self.add_bytes(new_size);
+    }
+
+    pub(crate) fn record_failure(&mut self) {
+        self.failures += 1;
+    }
+
+    fn add_bytes(&mut self, size: usize) {
+        self.bytes_in_use += size;
+        self.peak_bytes = self.peak_bytes.max(self.bytes_in_use);
+    }
+}
+
+/// Allocation recorded by the tracker
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LiveAllocation {
+    pub address: usize,
+    pub layout: Layout,
+    /// Sequence number of the allocation, compared with markers.
+    /// Reallocations keep the number of the original allocation.
+    pub id: u64,
+}
+
+/// Fixed capacity record of the live allocations, it cannot use the heap it is tracking
+///
+/// Allocations are kept in a hash table of their address with linear probing, so recording an
+/// allocation or a free does not scan the whole table.
+pub(crate) struct AllocationTracker {
+    entries: [Option<LiveAllocation>; TABLE_SIZE],
+    len: usize,
+    next_id: u64,
+    untracked: usize,
+}
+
+impl AllocationTracker {
+    pub(crate) const fn new() -> AllocationTracker {
+        AllocationTracker {
+            entries: [None; TABLE_SIZE],
+            len: 0,
+            next_id: 0,
+            untracked: 0,
+        }
+    }
+
+    /// Id that the next allocation will get
+    pub(crate) fn marker(&self) -> u64 {
+        self.next_id
+    }
+
+    /// Allocations that could not be recorded because the tracker was full
+    pub(crate) fn untracked(&self) -> usize {
+        self.untracked
+    }
+
+    pub(crate) fn insert(&mut self, address: usize, layout: Layout) {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.insert_with_id(address, layout, id);
+    }
+
+    pub(crate) fn remove(&mut self, address: usize) -> Option<LiveAllocation> {
+        let mut hole = self.find(address)?;
+        let removed = self.entries[hole].take();
+        self.len -= 1;
+
+        // Moves back the entries after the hole that cannot be found past it anymore
+        let mut i = hole;
+        loop {
+            i = (i + 1) % TABLE_SIZE;
+            let Some(entry) = self.entries[i] else {
+                break;
+            };
+
+            let from_home = i.wrapping_sub(slot(entry.address)) % TABLE_SIZE;
+            let from_hole = i.wrapping_sub(hole) % TABLE_SIZE;
+            if from_home >= from_hole {
+                self.entries[hole] = self.entries[i].take();
+                hole = i;
+            }
+        }
+
+        removed
+    }
+
+    /// Updates the allocation at `old_address` after a reallocation, untracked allocations stay untracked
+    pub(crate) fn moved(&mut self, old_address: usize, new_address: usize, layout: Layout) {
+        if let Some(old) = self.remove(old_address) {
+            self.insert_with_id(new_address, layout, old.id);
+        }
+    }
+
+    /// Recorded allocation with the lowest id in `[from, to)`
+    pub(crate) fn first_between(&self, from: u64, to: u64) -> Option<LiveAllocation> {
+        self.entries
+            .iter()
+            .flatten()
+            .filter(|e| (from..to).contains(&e.id))
+            .min_by_key(|e| e.id)
+            .copied()
+    }
+
+    fn insert_with_id(&mut self, address: usize, layout: Layout, id: u64) {
+        if self.len == TRACKED_ALLOCATIONS {
+            self.untracked += 1;
+            return;
+        }
+
+        let mut i = slot(address);
+        while self.entries[i].is_some() {
+            i = (i + 1) % TABLE_SIZE;
+        }
+
+        self.entries[i] = Some(LiveAllocation {
+            address,
+            layout,
+            id,
+        });
+        self.len += 1;
+    }
+
+    /// Slot of the allocation at `address`
+    fn find(&self, address: usize) -> Option<usize> {
+        let mut i = slot(address);
+        loop {
+            match self.entries[i] {
+                Some(entry) if entry.address == address => return Some(i),
+                Some(_) => i = (i + 1) % TABLE_SIZE,
+                None => return None,
+            }
+        }
+    }
+}
+
+/// First slot probed for `address`
+fn slot(address: usize) -> usize {
+    // Fibonacci hashing, the top bits of the product depend on every bit of the address
+    let hash = (address as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15);
+    (hash >> (64 - TABLE_SIZE.trailing_zeros())) as usize
+}
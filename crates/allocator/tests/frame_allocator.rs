@@ -0,0 +1,191 @@
+//! The frames are taken from a heap buffer, aligned so every order can be used
+
+use allocator::{FRAME_SIZE, FrameAllocator, MAX_ORDER, PhysAddr};
+
+const MAX_BLOCK_SIZE: usize = FRAME_SIZE << (MAX_ORDER - 1);
+
+/// Allocator managing `blocks` blocks of the biggest order, and the address of the first one
+fn allocator(blocks: usize) -> (FrameAllocator, PhysAddr) {
+    // Leaked so the frames live until the end of the tests
+    let buffer = vec![0u8; (blocks + 1) * MAX_BLOCK_SIZE].leak();
+    let start = (buffer.as_ptr() as usize).next_multiple_of(MAX_BLOCK_SIZE);
+
+    let mut allocator = FrameAllocator::new();
+    unsafe {
+        allocator.add_region(
+            PhysAddr::new(start),
+            PhysAddr::new(start + blocks * MAX_BLOCK_SIZE),
+        )
+    };
+    (allocator, PhysAddr::new(start))
+}
+
+fn single_block(order: usize) -> [usize; MAX_ORDER] {
+    let mut blocks = [0; MAX_ORDER];
+    blocks[order] = 1;
+    blocks
+}
+
+#[test]
+fn add_region_uses_biggest_blocks() {
+    let (allocator, _) = allocator(2);
+
+    assert_eq!(allocator.total_frames(), 2 << (MAX_ORDER - 1));
+    assert_eq!(allocator.free_frames(), allocator.total_frames());
+    assert_eq!(allocator.free_blocks()[MAX_ORDER - 1], 2);
+}
+
+#[test]
+fn add_region_ignores_partial_frames() {
+    let (_, start) = allocator(1);
+    let mut allocator = FrameAllocator::new();
+
+    unsafe { allocator.add_region(start.offset(1), start.offset(4 * FRAME_SIZE - 1)) };
+    assert_eq!(allocator.total_frames(), 2);
+    // Frames 1 and 2 are not buddies
+    assert_eq!(allocator.free_blocks()[0], 2);
+}
+
+#[test]
+fn split() {
+    let (mut allocator, start) = allocator(1);
+
+    assert_eq!(allocator.allocate(0), Some(start));
+    // One upper half is left for every order below the split block
+    let mut expected = [1; MAX_ORDER];
+    expected[MAX_ORDER - 1] = 0;
+    assert_eq!(allocator.free_blocks(), expected);
+    assert_eq!(allocator.free_frames(), allocator.total_frames() - 1);
+
+    // The buddy comes next, then the block of order 1 after it
+    assert_eq!(allocator.allocate(0), Some(start.offset(FRAME_SIZE)));
+    assert_eq!(allocator.allocate(1), Some(start.offset(2 * FRAME_SIZE)));
+}
+
+#[test]
+fn blocks_are_aligned_on_their_size() {
+    let (mut allocator, _) = allocator(1);
+    allocator.allocate(0).unwrap();
+
+    for order in 1..4 {
+        let address = allocator.allocate(order).unwrap();
+        assert!(address.as_usize().is_multiple_of(FRAME_SIZE << order));
+    }
+}
+
+#[test]
+fn buddy_merge() {
+    let (mut allocator, start) = allocator(1);
+    let a = allocator.allocate(0).unwrap();
+    let b = allocator.allocate(0).unwrap();
+
+    allocator.deallocate(a, 0);
+    assert_eq!(allocator.free_blocks()[0], 1);
+
+    // Freeing the buddy merges the blocks back up to the biggest order
+    allocator.deallocate(b, 0);
+    assert_eq!(allocator.free_blocks(), single_block(MAX_ORDER - 1));
+    assert_eq!(allocator.allocate(MAX_ORDER - 1), Some(start));
+}
+
+#[test]
+fn no_merge_with_allocated_buddy() {
+    let (mut allocator, _) = allocator(1);
+    let a = allocator.allocate(0).unwrap();
+    let _b = allocator.allocate(0).unwrap();
+    let before = allocator.free_blocks();
+
+    allocator.deallocate(a, 0);
+    let mut expected = before;
+    expected[0] += 1;
+    assert_eq!(allocator.free_blocks(), expected);
+}
+
+#[test]
+fn allocate_frames_gives_back_excess() {
+    let (mut allocator, start) = allocator(1);
+    let total = allocator.total_frames();
+
+    assert_eq!(allocator.allocate_frames(3), Some(start));
+    assert_eq!(allocator.free_frames(), total - 3);
+    // The 4th frame of the block is free again
+    assert_eq!(allocator.allocate(0), Some(start.offset(3 * FRAME_SIZE)));
+    assert_eq!(allocator.allocate_frames(0), None);
+}
+
+#[test]
+fn alloc_free_cycle() {
+    let (mut allocator, _) = allocator(2);
+    let initial = allocator.free_blocks();
+    let total = allocator.total_frames();
+
+    let blocks: Vec<_> = [0, 3, 1, 0, 5, 2, MAX_ORDER - 1]
+        .into_iter()
+        .map(|order| (allocator.allocate(order).unwrap(), order))
+        .collect();
+    let runs: Vec<_> = [3, 5, 1, 100]
+        .into_iter()
+        .map(|count| (allocator.allocate_frames(count).unwrap(), count))
+        .collect();
+
+    let used: usize = blocks.iter().map(|(_, order)| 1 << order).sum::<usize>()
+        + runs.iter().map(|(_, count)| count).sum::<usize>();
+    assert_eq!(allocator.free_frames(), total - used);
+
+    // Freed in another order than allocated
+    for &(address, count) in runs.iter().rev() {
+        allocator.deallocate_frames(address, count);
+    }
+    for &(address, order) in blocks
+        .iter()
+        .step_by(2)
+        .chain(blocks.iter().skip(1).step_by(2))
+    {
+        allocator.deallocate(address, order);
+    }
+
+    assert_eq!(allocator.free_frames(), total);
+    assert_eq!(allocator.free_blocks(), initial);
+}
+
+#[test]
+fn out_of_memory() {
+    let (mut allocator, _) = allocator(1);
+
+    assert!(allocator.allocate(MAX_ORDER - 1).is_some());
+    assert_eq!(allocator.allocate(0), None);
+    assert_eq!(allocator.allocate(MAX_ORDER), None);
+}
+
+// Only checked in debug builds
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "double free")]
+fn double_free() {
+    let (mut allocator, _) = allocator(1);
+    let a = allocator.allocate(0).unwrap();
+    let _b = allocator.allocate(0).unwrap();
+
+    allocator.deallocate(a, 0);
+    allocator.deallocate(a, 0);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "double free")]
+fn double_free_of_merged_block() {
+    let (mut allocator, _) = allocator(1);
+    let a = allocator.allocate(0).unwrap();
+
+    // Merged back in the block of the biggest order
+    allocator.deallocate(a, 0);
+    allocator.deallocate(a, 0);
+}
+
+#[test]
+#[should_panic(expected = "invalid order")]
+fn deallocate_invalid_order() {
+    let (mut allocator, start) = allocator(1);
+
+    allocator.deallocate(start, MAX_ORDER);
+}
@@ -0,0 +1,158 @@
+//! The heap is a buffer aligned on 4 KiB, so the addresses of the blocks are known
+
+use std::{alloc::Layout, ptr::NonNull};
+
+use allocator::{Allocator, LinkedListAllocator};
+
+const HEAP_SIZE: usize = 64 * 1024;
+const HEAP_ALIGN: usize = 4096;
+
+/// Allocator managing a fresh heap, and the start of the heap
+fn allocator() -> (LinkedListAllocator, usize) {
+    // Leaked so the blocks live until the end of the tests
+    let buffer = vec![0u8; HEAP_SIZE + HEAP_ALIGN].leak();
+    let start = (buffer.as_ptr() as usize).next_multiple_of(HEAP_ALIGN);
+
+    (LinkedListAllocator::init(start, start + HEAP_SIZE), start)
+}
+
+fn layout(size: usize) -> Layout {
+    Layout::from_size_align(size, 8).unwrap()
+}
+
+fn allocate(allocator: &mut LinkedListAllocator, size: usize) -> usize {
+    allocator.allocate(layout(size)).unwrap().as_ptr() as usize
+}
+
+fn deallocate(allocator: &mut LinkedListAllocator, address: usize, size: usize) {
+    allocator.deallocate(NonNull::new(address as *mut u8).unwrap(), layout(size));
+}
+
+#[test]
+fn first_fit_from_the_start() {
+    let (mut allocator, start) = allocator();
+
+    assert_eq!(allocator.get_available(), HEAP_SIZE);
+    assert_eq!(allocate(&mut allocator, 64), start);
+    assert_eq!(allocate(&mut allocator, 64), start + 64);
+    assert_eq!(allocator.get_available(), HEAP_SIZE - 128);
+}
+
+#[test]
+fn coalesce_with_previous() {
+    let (mut allocator, start) = allocator();
+    let a = allocate(&mut allocator, 64);
+    let b = allocate(&mut allocator, 64);
+    // Keeps `b` from merging with the rest of the heap
+    let _fence = allocate(&mut allocator, 64);
+
+    deallocate(&mut allocator, a, 64);
+    deallocate(&mut allocator, b, 64);
+
+    // Only a single block holds both
+    assert_eq!(allocate(&mut allocator, 128), start);
+}
+
+#[test]
+fn coalesce_with_next() {
+    let (mut allocator, start) = allocator();
+    let a = allocate(&mut allocator, 64);
+    let b = allocate(&mut allocator, 64);
+    let _fence = allocate(&mut allocator, 64);
+
+    deallocate(&mut allocator, b, 64);
+    deallocate(&mut allocator, a, 64);
+
+    assert_eq!(allocate(&mut allocator, 128), start);
+}
+
+#[test]
+fn coalesce_with_both_neighbours() {
+    let (mut allocator, start) = allocator();
+    let a = allocate(&mut allocator, 64);
+    let b = allocate(&mut allocator, 64);
+    let c = allocate(&mut allocator, 64);
+    let _fence = allocate(&mut allocator, 64);
+
+    deallocate(&mut allocator, a, 64);
+    deallocate(&mut allocator, c, 64);
+    deallocate(&mut allocator, b, 64);
+
+    assert_eq!(allocate(&mut allocator, 192), start);
+}
+
+#[test]
+fn available_restored_after_freeing_everything() {
+    let (mut allocator, start) = allocator();
+    let sizes = [24, 1, 512, 100, 8, 4000, 16];
+
+    let blocks: Vec<_> = sizes
+        .iter()
+        .map(|&size| (allocate(&mut allocator, size), size))
+        .collect();
+    assert!(allocator.get_available() < HEAP_SIZE);
+
+    // Every other block first, so frees land between allocated blocks
+    for &(address, size) in blocks
+        .iter()
+        .step_by(2)
+        .chain(blocks.iter().skip(1).step_by(2))
+    {
+        deallocate(&mut allocator, address, size);
+    }
+
+    assert_eq!(allocator.get_available(), HEAP_SIZE);
+    // The heap is a single block again
+    assert_eq!(allocate(&mut allocator, HEAP_SIZE), start);
+}
+
+#[test]
+fn small_allocations_take_a_free_block() {
+    let (mut allocator, _) = allocator();
+    let available = allocator.get_available();
+
+    // Rounded up so the block can hold the free list link once freed
+    let address = allocate(&mut allocator, 1);
+    assert_eq!(allocator.get_available(), available - 16);
+    deallocate(&mut allocator, address, 1);
+    assert_eq!(allocator.get_available(), available);
+}
+
+#[test]
+fn alignment_padding_is_given_back() {
+    let (mut allocator, start) = allocator();
+    allocate(&mut allocator, 48);
+
+    let aligned = allocator
+        .allocate(Layout::from_size_align(64, 64).unwrap())
+        .unwrap()
+        .as_ptr() as usize;
+    assert_eq!(aligned, start + 64);
+    assert_eq!(allocator.get_available(), HEAP_SIZE - 48 - 64);
+
+    // The padding is a free block, first in the list
+    assert_eq!(allocate(&mut allocator, 16), start + 48);
+}
+
+#[test]
+fn alignment_padding_too_small_for_a_block() {
+    let (mut allocator, start) = allocator();
+    allocate(&mut allocator, 24);
+
+    // 8 bytes of padding cannot hold a free block, the allocation moves to the next boundary
+    let aligned = allocator
+        .allocate(Layout::from_size_align(32, 32).unwrap())
+        .unwrap()
+        .as_ptr() as usize;
+    assert_eq!(aligned, start + 64);
+    assert_eq!(allocate(&mut allocator, 40), start + 24);
+}
+
+#[test]
+fn out_of_memory() {
+    let (mut allocator, _) = allocator();
+
+    assert!(allocator.allocate(layout(HEAP_SIZE + 8)).is_err());
+    allocate(&mut allocator, HEAP_SIZE);
+    assert!(allocator.allocate(layout(8)).is_err());
+}
@@ -0,0 +1,116 @@
+use std::{
+    alloc::{GlobalAlloc, Layout},
+    collections::BTreeSet,
+};
+
+use allocator::{GlobalAllocator, LinkedListAllocator, RegionSet, TRACKED_ALLOCATIONS};
+
+const HEAP_SIZE: usize = 1024 * 1024;
+
+fn allocator() -> Box<GlobalAllocator<LinkedListAllocator>> {
+    // Leaked so the blocks live until the end of the tests
+    let heap = vec![0u8; HEAP_SIZE].leak().as_ptr_range();
+    let mut regions = RegionSet::new();
+    regions.add(heap.start as usize, heap.end as usize);
+
+    let allocator = Box::new(GlobalAllocator::new());
+    allocator.init(&regions);
+    allocator.enable_tracking();
+    allocator
+}
+
+fn layout(size: usize) -> Layout {
+    Layout::from_size_align(size, 8).unwrap()
+}
+
+/// Addresses reported by `for_each_outstanding` since `marker`, checking they come oldest first
+fn outstanding(allocator: &GlobalAllocator<LinkedListAllocator>, marker: u64) -> BTreeSet<usize> {
+    let mut last_id = None;
+    let mut addresses = BTreeSet::new();
+    allocator.for_each_outstanding(marker, |allocation| {
+        assert!(last_id < Some(allocation.id));
+        last_id = Some(allocation.id);
+        addresses.insert(allocation.address);
+    });
+    addresses
+}
+
+#[test]
+fn disabled_by_default() {
+    let allocator = GlobalAllocator::<LinkedListAllocator>::new();
+
+    assert_eq!(allocator.marker(), None);
+    assert_eq!(allocator.untracked_allocations(), 0);
+}
+
+#[test]
+fn reports_live_allocations_since_marker() {
+    let allocator = allocator();
+    let before = unsafe { allocator.alloc(layout(32)) };
+    let marker = allocator.marker().unwrap();
+
+    let blocks: Vec<_> = (1..200)
+        .map(|i| unsafe { allocator.alloc(layout(i * 8)) })
+        .collect();
+    // Freed out of order, so entries move around in the table
+    let mut live = BTreeSet::new();
+    for (i, &block) in blocks.iter().enumerate() {
+        if i % 3 == 0 {
+            unsafe { allocator.dealloc(block, layout((i + 1) * 8)) };
+        } else {
+            live.insert(block as usize);
+        }
+    }
+
+    let reported = outstanding(&allocator, marker);
+    assert_eq!(reported, live);
+    assert!(!reported.contains(&(before as usize)));
+
+    for (i, &block) in blocks.iter().enumerate() {
+        if i % 3 != 0 {
+            unsafe { allocator.dealloc(block, layout((i + 1) * 8)) };
+        }
+    }
+    assert!(outstanding(&allocator, marker).is_empty());
+}
+
+#[test]
+fn reallocation_keeps_id() {
+    let allocator = allocator();
+    let marker = allocator.marker().unwrap();
+    let block = unsafe { allocator.alloc(layout(16)) };
+    // Keeps the block from growing in place
+    let _next = unsafe { allocator.alloc(layout(16)) };
+
+    let moved = unsafe { allocator.realloc(block, layout(16), 4096) };
+    let mut ids = Vec::new();
+    allocator.for_each_outstanding(marker, |allocation| {
+        ids.push((allocation.id, allocation.address));
+    });
+
+    assert_eq!(ids[0], (marker, moved as usize));
+}
+
+#[test]
+fn full_tracker_counts_untracked() {
+    let allocator = allocator();
+    let marker = allocator.marker().unwrap();
+
+    let blocks: Vec<_> = (0..TRACKED_ALLOCATIONS + 10)
+        .map(|_| unsafe { allocator.alloc(layout(16)) })
+        .collect();
+    assert_eq!(allocator.untracked_allocations(), 10);
+    assert_eq!(outstanding(&allocator, marker).len(), TRACKED_ALLOCATIONS);
+
+    for block in blocks {
+        unsafe { allocator.dealloc(block, layout(16)) };
+    }
+    assert!(outstanding(&allocator, marker).is_empty());
+
+    // Freed slots are used again
+    let block = unsafe { allocator.alloc(layout(16)) };
+    assert_eq!(
+        outstanding(&allocator, marker),
+        BTreeSet::from([block as usize])
+    );
+}
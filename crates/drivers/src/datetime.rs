@@ -0,0 +1,166 @@
+use core::{
+    fmt::{self, Display},
+    time::Duration,
+};
+
+const SECS_PER_DAY: u64 = 86_400;
+// Days from 0000-03-01 to 1970-01-01 in the proleptic Gregorian calendar
+const DAYS_TO_EPOCH: u64 = 719_468;
+const DAYS_PER_ERA: u64 = 146_097;
+
+/// Time elapsed since the UNIX epoch, 1970-01-01T00:00:00Z, leap seconds excluded
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct UnixTime(Duration);
+
+impl UnixTime {
+    pub const EPOCH: UnixTime = UnixTime(Duration::ZERO);
+
+    pub const fn from_secs(secs: u64) -> UnixTime {
+        UnixTime(Duration::from_secs(secs))
+    }
+
+    pub const fn from_nanos(nanos: u64) -> UnixTime {
+        UnixTime(Duration::from_nanos(nanos))
+    }
+
+    pub const fn from_duration(since_epoch: Duration) -> UnixTime {
+        UnixTime(since_epoch)
+    }
+
+    pub const fn since_epoch(&self) -> Duration {
+        self.0
+    }
+
+    pub const fn as_secs(&self) -> u64 {
+        self.0.as_secs()
+    }
+
+    /// Nanoseconds since the epoch, `None` after year 2554
+    pub fn as_nanos(&self) -> Option<u64> {
+        self.0.as_nanos().try_into().ok()
+    }
+
+    pub fn checked_add(&self, duration: Duration) -> Option<UnixTime> {
+        self.0.checked_add(duration).map(UnixTime)
+    }
+
+    /// Broken-down UTC date and time
+    pub fn to_utc(self) -> DateTime {
+        let secs = self.0.as_secs();
+        let (year, month, day) = civil_from_days(secs / SECS_PER_DAY);
+        let secs_of_day = secs % SECS_PER_DAY;
+
+        DateTime {
+            year,
+            month,
+            day,
+            hour: (secs_of_day / 3600) as u8,
+            minute: (secs_of_day / 60 % 60) as u8,
+            second: (secs_of_day % 60) as u8,
+            nanosecond: self.0.subsec_nanos(),
+        }
+    }
+
+    /// `None` for an invalid date or one before the epoch
+    pub fn from_utc(date: &DateTime) -> Option<UnixTime> {
+        let valid = date.year >= 1970
+            && (1..=12).contains(&date.month)
+            && (1..=days_in_month(date.year, date.month)).contains(&date.day)
+            && date.hour < 24
+            && date.minute < 60
+            && date.second < 60
+            && date.nanosecond < 1_000_000_000;
+        if !valid {
+            return None;
+        }
+
+        let days = days_from_civil(date.year, date.month, date.day);
+        let secs = days
+            .checked_mul(SECS_PER_DAY)?
+            .checked_add(date.hour as u64 * 3600 + date.minute as u64 * 60 + date.second as u64)?;
+
+        Some(UnixTime(Duration::new(secs, date.nanosecond)))
+    }
+}
+
+/// Date and time in UTC, like an RTC counting in calendar fields
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DateTime {
+    pub year: u64,
+    /// 1 to 12
+    pub month: u8,
+    /// 1 to 31
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+    pub nanosecond: u32,
+}
+
+/// ISO 8601, like `2025-01-31T23:59:59Z`. The precision gives the number of digits of the
+/// fraction of second: `{:.3}` writes milliseconds.
+impl Display for DateTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+            self.year, self.month, self.day, self.hour, self.minute, self.second
+        )?;
+
+        if let Some(digits) = f.precision().filter(|&d| d > 0).map(|d| d.min(9)) {
+            let fraction = self.nanosecond / 10u32.pow(9 - digits as u32);
+            write!(f, ".{fraction:0digits$}")?;
+        }
+
+        f.write_str("Z")
+    }
+}
+
+fn is_leap_year(year: u64) -> bool {
+    year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400))
+}
+
+fn days_in_month(year: u64, month: u8) -> u8 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+// NOTE: Both conversions are the algorithms of Howard Hinnant, "chrono-Compatible Low-Level
+// Date Algorithms", with years starting in March so the leap day is the last of the year.
+
+/// Year, month and day of `days` since the epoch
+fn civil_from_days(days: u64) -> (u64, u8, u8) {
+    let days = days + DAYS_TO_EPOCH;
+    let era = days / DAYS_PER_ERA;
+    let day_of_era = days - era * DAYS_PER_ERA;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let march_month = (5 * day_of_year + 2) / 153;
+
+    let day = (day_of_year - (153 * march_month + 2) / 5 + 1) as u8;
+    let month = if march_month < 10 {
+        march_month + 3
+    } else {
+        march_month - 9
+    } as u8;
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+
+    (year, month, day)
+}
+
+/// Days since the epoch of a valid date after it
+fn days_from_civil(year: u64, month: u8, day: u8) -> u64 {
+    let year = year - u64::from(month <= 2);
+    let era = year / 400;
+    let year_of_era = year - era * 400;
+    let march_month = if month > 2 { month - 3 } else { month + 9 } as u64;
+    let day_of_year = (153 * march_month + 2) / 5 + day as u64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    era * DAYS_PER_ERA + day_of_era - DAYS_TO_EPOCH
+}
@@ -1,9 +1,32 @@
 use core::fmt::Write;
 
-use crate::driver::Driver;
+use crate::{
+    datetime::{DateTime, UnixTime},
+    driver::Driver,
+};
+
+/// Why `UartDriver::set_baud` failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BaudError {
+    /// The input clock of the device is not known
+    UnknownClock,
+    /// The rate cannot be derived from the input clock
+    Unsupported(u32),
+}
 
 pub trait UartDriver: Driver + Write {
-    fn set_baud(&mut self, baud: u32);
+    fn set_baud(&mut self, baud: u32) -> Result<(), BaudError>;
     fn put_char(&mut self, c: char);
     fn get_char(&mut self) -> Option<char>;
 }
+
+/// Real-time clock, keeps the wall-clock time while the system is off
+pub trait RtcDriver: Driver {
+    fn read_time(&mut self) -> UnixTime;
+    fn set_time(&mut self, time: UnixTime);
+
+    /// Current date and time in UTC
+    fn read_utc(&mut self) -> DateTime {
+        self.read_time().to_utc()
+    }
+}
@@ -0,0 +1,73 @@
+use core::ptr;
+
+use alloc::sync::Arc;
+use dtb_reader::DeviceTreeNode;
+use spin::Mutex;
+
+use crate::{DriverManager, datetime::UnixTime, driver::Driver, driver_capabilities::RtcDriver};
+
+// Registers, the time is in nanoseconds since the UNIX epoch
+// NOTE: The alarm registers are left alone, nothing routes the interrupts of devices yet
+const TIME_LOW: usize = 0x00;
+const TIME_HIGH: usize = 0x04;
+
+/// RTC of the Android emulator, also found on QEMU virt
+#[derive(Debug)]
+pub struct GoldfishRtc {
+    address: usize,
+}
+
+impl Driver for GoldfishRtc {
+    fn try_initialize(node: &DeviceTreeNode, path: &str, manager: &mut DriverManager) -> bool {
+        let address = node
+            .reg()
+            .next()
+            .and_then(|(address, _)| node.translate_address(address));
+
+        if let Some(address) = address {
+            let concrete_driver = GoldfishRtc {
+                address: address as usize,
+            };
+            let shared_driver = Arc::new(Mutex::new(concrete_driver));
+
+            let as_rtc: Arc<Mutex<dyn RtcDriver>> = shared_driver;
+            manager.register_capability::<dyn RtcDriver>(path, as_rtc);
+
+            true
+        } else {
+            false
+        }
+    }
+
+    fn compatible() -> &'static [&'static str] {
+        &["google,goldfish-rtc"]
+    }
+}
+
+impl RtcDriver for GoldfishRtc {
+    fn read_time(&mut self) -> UnixTime {
+        // Reading the low half latches the high half
+        let low = self.read(TIME_LOW) as u64;
+        let high = self.read(TIME_HIGH) as u64;
+
+        UnixTime::from_nanos(high << 32 | low)
+    }
+
+    fn set_time(&mut self, time: UnixTime) {
+        let nanos = time.as_nanos().unwrap_or(u64::MAX);
+
+        // Writing the low half sets the time
+        self.write(TIME_HIGH, (nanos >> 32) as u32);
+        self.write(TIME_LOW, nanos as u32);
+    }
+}
+
+impl GoldfishRtc {
+    fn read(&self, register: usize) -> u32 {
+        unsafe { ptr::read_volatile((self.address + register) as *const u32) }
+    }
+
+    fn write(&mut self, register: usize, value: u32) {
+        unsafe { ptr::write_volatile((self.address + register) as *mut u32, value) }
+    }
+}
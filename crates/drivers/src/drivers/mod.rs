@@ -1 +1,2 @@
+pub mod goldfish_rtc;
 pub mod ns16550a;
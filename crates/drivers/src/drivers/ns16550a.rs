@@ -4,27 +4,45 @@ use alloc::sync::Arc;
 use dtb_reader::DeviceTreeNode;
 use spin::Mutex;
 
-use crate::{DriverManager, driver::Driver, driver_capabilities::UartDriver};
+use crate::{
+    DriverManager,
+    driver::Driver,
+    driver_capabilities::{BaudError, UartDriver},
+};
+
+// Registers, the divisor latch replaces the first two while `LCR_DLAB` is set
+const RBR_THR: usize = 0;
+const DLL: usize = 0;
+const DLM: usize = 1;
+const LCR: usize = 3;
+const LSR: usize = 5;
+
+const LCR_DLAB: u8 = 0x80;
+const LSR_DATA_READY: u8 = 0x01;
 
 #[derive(Debug)]
 pub struct Ns16550a {
     address: usize,
+    /// Input clock in Hz, from `clock-frequency`
+    clock_frequency: Option<u32>,
 }
 
 impl Driver for Ns16550a {
     fn try_initialize(node: &DeviceTreeNode, path: &str, manager: &mut DriverManager) -> bool {
-        let address: Option<usize> = node.get_property("reg").and_then(|prop| {
-            let bytes = prop.raw_value();
-            if bytes.len() >= 4 {
-                let addr_bytes: [u8; 4] = bytes[4..8].try_into().ok()?;
-                Some(u32::from_be_bytes(addr_bytes) as usize)
-            } else {
-                None
-            }
-        });
+        // The registers are at the start of the first `reg` entry
+        let address = node
+            .reg()
+            .next()
+            .and_then(|(address, _)| node.translate_address(address));
 
         if let Some(address) = address {
-            let concrete_driver = Ns16550a { address };
+            let concrete_driver = Ns16550a {
+                address: address as usize,
+                clock_frequency: node
+                    .get_property("clock-frequency")
+                    .and_then(|p| p.as_u32().ok())
+                    .filter(|&f| f != 0),
+            };
             let shared_driver = Arc::new(Mutex::new(concrete_driver));
 
             let as_uart: Arc<Mutex<dyn UartDriver>> = shared_driver;
@@ -43,17 +61,44 @@ impl Driver for Ns16550a {
 
 impl UartDriver for Ns16550a {
     fn put_char(&mut self, c: char) {
-        unsafe {
-            ptr::write_volatile(self.address as *mut u8, c as u8);
-        }
+        self.write(RBR_THR, c as u8);
     }
 
     fn get_char(&mut self) -> Option<char> {
-        todo!()
+        if self.read(LSR) & LSR_DATA_READY == 0 {
+            return None;
+        }
+
+        Some(self.read(RBR_THR) as char)
+    }
+
+    fn set_baud(&mut self, baud: u32) -> Result<(), BaudError> {
+        let clock = self.clock_frequency.ok_or(BaudError::UnknownClock)?;
+
+        // The clock is divided by 16 times the divisor, rounded to the nearest rate
+        let divisor = (baud != 0)
+            .then(|| (clock as u64 + 8 * baud as u64) / (16 * baud as u64))
+            .and_then(|divisor| u16::try_from(divisor).ok())
+            .filter(|&divisor| divisor != 0)
+            .ok_or(BaudError::Unsupported(baud))?;
+
+        let lcr = self.read(LCR);
+        self.write(LCR, lcr | LCR_DLAB);
+        self.write(DLL, divisor as u8);
+        self.write(DLM, (divisor >> 8) as u8);
+        self.write(LCR, lcr & !LCR_DLAB);
+
+        Ok(())
+    }
+}
+
+impl Ns16550a {
+    fn read(&self, register: usize) -> u8 {
+        unsafe { ptr::read_volatile((self.address + register) as *const u8) }
     }
 
-    fn set_baud(&mut self, _baud: u32) {
-        todo!()
+    fn write(&mut self, register: usize, value: u8) {
+        unsafe { ptr::write_volatile((self.address + register) as *mut u8, value) }
     }
 }
 
@@ -2,11 +2,13 @@
 
 extern crate alloc;
 
+mod datetime;
 mod driver;
 mod driver_capabilities;
 mod drivers;
 mod manager;
 mod registry;
 
+pub use datetime::{DateTime, UnixTime};
 pub use driver_capabilities::*;
 pub use manager::DriverManager;
@@ -1,10 +1,10 @@
 use core::any::{Any, TypeId};
 
 use alloc::{
-    collections::{btree_map::BTreeMap, vec_deque::VecDeque},
-    format,
+    collections::btree_map::BTreeMap,
     string::{String, ToString},
     sync::Arc,
+    vec::Vec,
 };
 use dtb_reader::DeviceTreeNode;
 use log::info;
@@ -12,9 +12,19 @@ use spin::Mutex;
 
 use crate::registry::get_registry;
 
+/// What `load_drivers` did with a node
+#[derive(Debug, Clone, Copy)]
+enum Probe {
+    Loaded,
+    /// Disabled, with its children
+    Skipped,
+}
+
 #[derive(Default)]
 pub struct DriverManager {
     drivers: BTreeMap<(String, TypeId), Arc<dyn Any + Send + Sync>>,
+    // Nodes loaded or skipped by `load_drivers` in tree order, kept for `log`
+    probes: Vec<(String, Probe)>,
 }
 
 impl DriverManager {
@@ -22,28 +32,49 @@ impl DriverManager {
         Self::default()
     }
 
-    /// Load drivers based on Device Tree
+    /// Load drivers based on Device Tree, see `log` for the nodes it loaded or skipped
     pub fn load_drivers(&mut self, dtb_root: &DeviceTreeNode) {
-        let mut queue: VecDeque<(DeviceTreeNode, String)> = VecDeque::new();
+        // Path of the current node, built from the one of its parent. `path_lengths[i]` is the
+        // length of the path of the node at depth `i + 1` leading to it.
+        let mut path = match dtb_root.depth() {
+            0 => String::new(),
+            _ => dtb_root.path().to_string(),
+        };
+        let root_length = path.len();
+        let mut path_lengths = Vec::new();
+        // Depth of the disabled node whose subtree is being skipped
+        let mut disabled_depth = None;
+
+        for (depth, node) in dtb_root.descendants() {
+            match disabled_depth {
+                Some(disabled) if depth > disabled => continue,
+                _ => disabled_depth = None,
+            }
 
-        for child in dtb_root.children() {
-            queue.push_back((child, String::new()));
-        }
+            path_lengths.truncate(depth - 1);
+            path.truncate(path_lengths.last().copied().unwrap_or(root_length));
+            path.push('/');
+            path.push_str(node.full_name());
+            path_lengths.push(path.len());
 
-        while let Some((node, parent_path)) = queue.pop_front() {
-            let node_name = node.full_name();
-            let current_path = if parent_path.is_empty() {
-                format!("/{}", node_name)
-            } else {
-                format!("{}/{}", parent_path, node_name)
-            };
+            if !node.is_enabled() {
+                self.probes.push((path.clone(), Probe::Skipped));
+                disabled_depth = Some(depth);
+                continue;
+            }
 
-            if self.try_init_driver(&node, &current_path) {
-                info!("Loaded driver for '{current_path}'");
+            if self.try_init_driver(&node, &path) {
+                self.probes.push((path.clone(), Probe::Loaded));
             }
+        }
+    }
 
-            for child in node.children() {
-                queue.push_back((child, current_path.clone()));
+    /// Logs the result of `load_drivers`, the logger is usually one of the drivers it loads
+    pub fn log(&self) {
+        for (path, probe) in &self.probes {
+            match probe {
+                Probe::Loaded => info!("Loaded driver for '{path}'"),
+                Probe::Skipped => info!("Skipped disabled '{path}' and its children"),
             }
         }
     }
@@ -56,11 +87,9 @@ impl DriverManager {
             None => return false,
         };
 
-        let compatibles = prop
-            .raw_value()
-            .split(|&b| b == 0)
-            .filter_map(|bytes| core::str::from_utf8(bytes).ok())
-            .filter(|s| !s.is_empty());
+        let Ok(compatibles) = prop.as_str_list() else {
+            return false;
+        };
 
         for compatible in compatibles {
             if let Some(init_fn) = registry.get_compatible(compatible)
@@ -86,6 +115,20 @@ impl DriverManager {
         self.drivers.insert((path.to_string(), type_id), stored);
     }
 
+    /// Gets the driver with the first path, in lexicographic order, that has a specific
+    /// capability. For devices usually found once, like an RTC.
+    pub fn get_first<T: ?Sized + 'static>(&self) -> Option<Arc<Mutex<T>>>
+    where
+        Arc<Mutex<T>>: Send + Sync,
+    {
+        let type_id = TypeId::of::<T>();
+        self.drivers
+            .iter()
+            .filter(|((_, id), _)| *id == type_id)
+            .find_map(|(_, entry)| entry.downcast_ref::<Arc<Mutex<T>>>())
+            .cloned()
+    }
+
     /// Gets a driver by path, cast to a specific trait.
     ///
     /// Example:
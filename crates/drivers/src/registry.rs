@@ -3,7 +3,11 @@ use alloc::{boxed::Box, collections::btree_map::BTreeMap};
 use dtb_reader::DeviceTreeNode;
 use spin::Once;
 
-use crate::{DriverManager, driver::Driver, drivers::ns16550a::Ns16550a};
+use crate::{
+    DriverManager,
+    driver::Driver,
+    drivers::{goldfish_rtc::GoldfishRtc, ns16550a::Ns16550a},
+};
 
 type DriverInitFn = Box<dyn Fn(&DeviceTreeNode, &str, &mut DriverManager) -> bool + Send + Sync>;
 
@@ -46,6 +50,7 @@ pub fn get_registry() -> &'static DriverRegistry {
         let mut registry = DriverRegistry::new();
 
         registry.register_driver::<Ns16550a>();
+        registry.register_driver::<GoldfishRtc>();
 
         registry
     })
@@ -0,0 +1,131 @@
+use std::time::Duration;
+
+use drivers::{DateTime, UnixTime};
+
+fn date(year: u64, month: u8, day: u8) -> DateTime {
+    DateTime {
+        year,
+        month,
+        day,
+        hour: 0,
+        minute: 0,
+        second: 0,
+        nanosecond: 0,
+    }
+}
+
+#[test]
+fn epoch() {
+    assert_eq!(UnixTime::EPOCH.to_utc(), date(1970, 1, 1));
+    assert_eq!(UnixTime::from_utc(&date(1970, 1, 1)), Some(UnixTime::EPOCH));
+    assert_eq!(UnixTime::EPOCH.to_utc().to_string(), "1970-01-01T00:00:00Z");
+}
+
+#[test]
+fn leap_day() {
+    let time = UnixTime::from_secs(951_782_400);
+
+    assert_eq!(time.to_utc(), date(2000, 2, 29));
+    assert_eq!(UnixTime::from_utc(&date(2000, 2, 29)), Some(time));
+    assert_eq!(
+        UnixTime::from_secs(1_709_164_800).to_utc(),
+        date(2024, 2, 29)
+    );
+}
+
+#[test]
+fn non_leap_century() {
+    // 2100 is not a leap year, February 28 is followed by March 1
+    assert_eq!(
+        UnixTime::from_secs(4_107_456_000).to_utc(),
+        date(2100, 2, 28)
+    );
+    assert_eq!(
+        UnixTime::from_secs(4_107_456_000 + 86_400).to_utc(),
+        date(2100, 3, 1)
+    );
+    assert_eq!(
+        UnixTime::from_utc(&date(2100, 3, 1)),
+        Some(UnixTime::from_secs(4_107_542_400))
+    );
+    assert_eq!(UnixTime::from_utc(&date(2100, 2, 29)), None);
+}
+
+#[test]
+fn time_of_day() {
+    let time = UnixTime::from_nanos(1_700_000_000_123_456_789);
+
+    assert_eq!(
+        time.to_utc(),
+        DateTime {
+            year: 2023,
+            month: 11,
+            day: 14,
+            hour: 22,
+            minute: 13,
+            second: 20,
+            nanosecond: 123_456_789,
+        }
+    );
+}
+
+#[test]
+fn round_trip() {
+    // Every day over 1200 years, at a time that changes with the day
+    for day in (0..438_300).step_by(7) {
+        let secs = day * 86_400 + day % 86_400;
+        let time = UnixTime::from_secs(secs)
+            .checked_add(Duration::from_nanos(day))
+            .unwrap();
+
+        assert_eq!(UnixTime::from_utc(&time.to_utc()), Some(time), "{secs}");
+    }
+}
+
+#[test]
+fn invalid_dates() {
+    let valid = date(2023, 6, 15);
+    let invalid = [
+        DateTime {
+            year: 1969,
+            ..valid
+        },
+        DateTime { month: 0, ..valid },
+        DateTime { month: 13, ..valid },
+        DateTime { day: 0, ..valid },
+        DateTime { day: 31, ..valid },
+        DateTime { hour: 24, ..valid },
+        DateTime {
+            minute: 60,
+            ..valid
+        },
+        DateTime {
+            second: 60,
+            ..valid
+        },
+        DateTime {
+            nanosecond: 1_000_000_000,
+            ..valid
+        },
+        date(2023, 2, 29),
+    ];
+
+    assert!(UnixTime::from_utc(&valid).is_some());
+    for date in invalid {
+        assert_eq!(UnixTime::from_utc(&date), None, "{date:?}");
+    }
+}
+
+#[test]
+fn display_precision() {
+    let date = UnixTime::from_nanos(1_700_000_000_123_456_789).to_utc();
+
+    assert_eq!(date.to_string(), "2023-11-14T22:13:20Z");
+    assert_eq!(format!("{date:.3}"), "2023-11-14T22:13:20.123Z");
+    assert_eq!(format!("{date:.9}"), "2023-11-14T22:13:20.123456789Z");
+    assert_eq!(format!("{date:.12}"), "2023-11-14T22:13:20.123456789Z");
+    assert_eq!(format!("{date:.0}"), "2023-11-14T22:13:20Z");
+    // Leading zeros of the fraction are kept
+    let date = UnixTime::from_nanos(5_000_000).to_utc();
+    assert_eq!(format!("{date:.3}"), "1970-01-01T00:00:00.005Z");
+}
@@ -0,0 +1,278 @@
+use alloc::{collections::btree_map::BTreeMap, string::String, vec::Vec};
+use core::fmt::{self, Display};
+
+use crate::{
+    DeviceTreeNode,
+    reader::{DTB_VERSION, HEADER_SIZE, MAGIC_VALUE},
+    tree::Tokens,
+};
+
+// Oldest version a reader of version 17 blobs must support
+const LAST_COMPATIBLE_VERSION: u32 = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BuildError {
+    /// `end_node` without a matching `begin_node`
+    UnbalancedEndNode,
+    /// Nodes still open at `finish`
+    UnclosedNodes(usize),
+    /// A second root node after the first one was closed
+    MultipleRoots,
+    PropertyOutsideNode,
+    /// The properties of a node must come before its children
+    PropertyAfterChild,
+    EmptyTree,
+    /// A block does not fit in the 32 bits offsets of the header
+    TooLarge,
+}
+
+impl Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BuildError::UnbalancedEndNode => write!(f, "end_node without begin_node"),
+            BuildError::UnclosedNodes(count) => write!(f, "{count} nodes not closed"),
+            BuildError::MultipleRoots => write!(f, "more than one root node"),
+            BuildError::PropertyOutsideNode => write!(f, "property outside of a node"),
+            BuildError::PropertyAfterChild => write!(f, "property after a child node"),
+            BuildError::EmptyTree => write!(f, "no root node"),
+            BuildError::TooLarge => write!(f, "blob larger than 4 GiB"),
+        }
+    }
+}
+
+/// Builds a version 17 Flattened Device Tree blob that `DtbReader` can read.
+///
+/// Calls can be chained, the first misuse is reported by `finish`:
+///
+/// ```rust
+/// let blob = FdtBuilder::new()
+///     .begin_node("")
+///     .property_u32("#address-cells", 2)
+///     .begin_node("chosen")
+///     .property_str("bootargs", "console=ttyS0")
+///     .end_node()
+///     .end_node()
+///     .finish()?;
+/// ```
+#[derive(Debug, Default)]
+pub struct FdtBuilder {
+    boot_cpuid_phys: u32,
+    reserve_map: Vec<(u64, u64)>,
+    structure: Vec<u8>,
+    strings: Vec<u8>,
+    // Offset of every name in `strings`, names are only stored once
+    string_offsets: BTreeMap<String, u32>,
+    // Number of open nodes
+    depth: usize,
+    // Whether the current node already has a child
+    has_child: bool,
+    root_closed: bool,
+    error: Option<BuildError>,
+}
+
+impl FdtBuilder {
+    pub fn new() -> FdtBuilder {
+        FdtBuilder::default()
+    }
+
+    /// Physical id of the boot hart written in the header
+    pub fn boot_cpuid_phys(&mut self, id: u32) -> &mut Self {
+        self.boot_cpuid_phys = id;
+        self
+    }
+
+    /// Adds an entry to the memory reservation block
+    pub fn reserve_memory(&mut self, address: u64, size: u64) -> &mut Self {
+        self.reserve_map.push((address, size));
+        self
+    }
+
+    /// Opens a node, the first one is the root and must be named `""`
+    pub fn begin_node(&mut self, name: &str) -> &mut Self {
+        if self.depth == 0 && self.root_closed {
+            self.fail(BuildError::MultipleRoots);
+        }
+
+        self.push_token(Tokens::BeginNode);
+        self.structure.extend_from_slice(name.as_bytes());
+        self.structure.push(0);
+        self.pad_structure();
+
+        self.depth += 1;
+        self.has_child = false;
+        self
+    }
+
+    /// Closes the last opened node
+    pub fn end_node(&mut self) -> &mut Self {
+        if self.depth == 0 {
+            self.fail(BuildError::UnbalancedEndNode);
+            return self;
+        }
+
+        self.push_token(Tokens::EndNode);
+
+        self.depth -= 1;
+        // The parent of the closed node has a child
+        self.has_child = true;
+        if self.depth == 0 {
+            self.root_closed = true;
+        }
+        self
+    }
+
+    /// Adds a property with a raw value to the current node
+    pub fn property(&mut self, name: &str, value: &[u8]) -> &mut Self {
+        if self.depth == 0 {
+            self.fail(BuildError::PropertyOutsideNode);
+        } else if self.has_child {
+            self.fail(BuildError::PropertyAfterChild);
+        }
+
+        let name_offset = self.string_offset(name);
+
+        self.push_token(Tokens::Property);
+        self.push_u32(value.len() as u32);
+        self.push_u32(name_offset);
+        self.structure.extend_from_slice(value);
+        self.pad_structure();
+        self
+    }
+
+    /// Boolean property, without value
+    pub fn property_empty(&mut self, name: &str) -> &mut Self {
+        self.property(name, &[])
+    }
+
+    pub fn property_u32(&mut self, name: &str, value: u32) -> &mut Self {
+        self.property(name, &value.to_be_bytes())
+    }
+
+    pub fn property_u64(&mut self, name: &str, value: u64) -> &mut Self {
+        self.property(name, &value.to_be_bytes())
+    }
+
+    pub fn property_cells(&mut self, name: &str, cells: &[u32]) -> &mut Self {
+        let value: Vec<u8> = cells.iter().flat_map(|c| c.to_be_bytes()).collect();
+        self.property(name, &value)
+    }
+
+    pub fn property_str(&mut self, name: &str, value: &str) -> &mut Self {
+        self.property_str_list(name, &[value])
+    }
+
+    /// NUL separated strings, like `compatible`
+    pub fn property_str_list(&mut self, name: &str, values: &[&str]) -> &mut Self {
+        let mut value = Vec::new();
+        for string in values {
+            value.extend_from_slice(string.as_bytes());
+            value.push(0);
+        }
+        self.property(name, &value)
+    }
+
+    /// Adds every property of `node` to the current node
+    pub fn copy_properties(&mut self, node: &DeviceTreeNode) -> &mut Self {
+        for prop in node.properties() {
+            self.property(prop.name(), prop.raw_value());
+        }
+        self
+    }
+
+    /// Adds `node` and all its descendants as a child of the current node, or as the root
+    pub fn copy_node(&mut self, node: &DeviceTreeNode) -> &mut Self {
+        self.begin_node(node.full_name());
+        self.copy_properties(node);
+        for child in node.children() {
+            self.copy_node(&child);
+        }
+        self.end_node()
+    }
+
+    /// Writes the blob
+    ///
+    /// # Errors
+    ///
+    /// Returns the first misuse of the builder.
+    pub fn finish(&self) -> Result<Vec<u8>, BuildError> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+        if self.depth != 0 {
+            return Err(BuildError::UnclosedNodes(self.depth));
+        }
+        if !self.root_closed {
+            return Err(BuildError::EmptyTree);
+        }
+
+        // Header, memory reservation block, structure block, strings block
+        let off_mem_rsvmap = HEADER_SIZE;
+        let off_dt_struct = off_mem_rsvmap + (self.reserve_map.len() + 1) * 16;
+        let size_dt_struct = self.structure.len() + 4;
+        let off_dt_strings = off_dt_struct + size_dt_struct;
+        let totalsize = off_dt_strings + self.strings.len();
+
+        let field = |value: usize| u32::try_from(value).map_err(|_| BuildError::TooLarge);
+        let header = [
+            MAGIC_VALUE,
+            field(totalsize)?,
+            field(off_dt_struct)?,
+            field(off_dt_strings)?,
+            field(off_mem_rsvmap)?,
+            DTB_VERSION,
+            LAST_COMPATIBLE_VERSION,
+            self.boot_cpuid_phys,
+            field(self.strings.len())?,
+            field(size_dt_struct)?,
+        ];
+
+        let mut blob = Vec::with_capacity(totalsize);
+        for value in header {
+            blob.extend_from_slice(&value.to_be_bytes());
+        }
+        // The terminating entry is all zeroes
+        for (address, size) in self.reserve_map.iter().chain(&[(0, 0)]) {
+            blob.extend_from_slice(&address.to_be_bytes());
+            blob.extend_from_slice(&size.to_be_bytes());
+        }
+        blob.extend_from_slice(&self.structure);
+        blob.extend_from_slice(&(Tokens::End as u32).to_be_bytes());
+        blob.extend_from_slice(&self.strings);
+
+        Ok(blob)
+    }
+
+    //
+    // NON-PUBLIC INTERFACE
+    //
+
+    fn fail(&mut self, error: BuildError) {
+        self.error.get_or_insert(error);
+    }
+
+    fn push_token(&mut self, token: Tokens) {
+        self.push_u32(token as u32);
+    }
+
+    fn push_u32(&mut self, value: u32) {
+        self.structure.extend_from_slice(&value.to_be_bytes());
+    }
+
+    /// Pads the structure block to the next token
+    fn pad_structure(&mut self) {
+        let padded = self.structure.len().next_multiple_of(4);
+        self.structure.resize(padded, 0);
+    }
+
+    fn string_offset(&mut self, name: &str) -> u32 {
+        if let Some(&offset) = self.string_offsets.get(name) {
+            return offset;
+        }
+
+        let offset = self.strings.len() as u32;
+        self.strings.extend_from_slice(name.as_bytes());
+        self.strings.push(0);
+        self.string_offsets.insert(String::from(name), offset);
+        offset
+    }
+}
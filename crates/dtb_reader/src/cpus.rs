@@ -0,0 +1,223 @@
+use crate::{
+    DeviceTreeNode, DtbReader, NodeProperty,
+    isa::{IsaExtension, IsaExtensions, IsaStringNames},
+};
+
+impl<'a> DtbReader<'a> {
+    /// Every hart of `/cpus`, disabled ones included
+    pub fn cpus(&self) -> impl Iterator<Item = CpuInfo<'a>> + use<'a> {
+        let cpus = self.cpus_node();
+        cpus.children()
+            .filter(|node| {
+                node.get_property("device_type")
+                    .and_then(|p| p.value_str())
+                    .is_some_and(|t| t == "cpu")
+            })
+            .map(move |node| CpuInfo { node, cpus })
+    }
+
+    pub fn cpu_by_hart_id(&self, hart_id: u64) -> Option<CpuInfo<'a>> {
+        self.cpus().find(|cpu| cpu.hart_id() == Some(hart_id))
+    }
+
+    /// Frequency of the `time` CSR in Hz, from `/cpus`
+    pub fn timebase_frequency(&self) -> Option<u64> {
+        frequency(self.cpus_node().get_property("timebase-frequency")?)
+    }
+}
+
+/// Hart described by a `cpu` node of `/cpus`
+#[derive(Debug, Clone, Copy)]
+pub struct CpuInfo<'a> {
+    node: DeviceTreeNode<'a>,
+    // Parent of `node`
+    cpus: DeviceTreeNode<'a>,
+}
+
+impl<'a> CpuInfo<'a> {
+    pub fn node(&self) -> DeviceTreeNode<'a> {
+        self.node
+    }
+
+    /// Hart ID, from `reg`
+    pub fn hart_id(&self) -> Option<u64> {
+        let reg = self.node.get_property("reg")?;
+        match self.cpus.address_cells() {
+            1 => reg.as_u32().ok().map(u64::from),
+            2 => reg.as_u64().ok(),
+            _ => None,
+        }
+    }
+
+    /// Value of `status`, `"okay"` when absent
+    pub fn status(&self) -> &'a str {
+        self.node
+            .get_property("status")
+            .and_then(|p| p.value_str())
+            .unwrap_or("okay")
+    }
+
+    /// Whether the hart can be started, see `DeviceTreeNode::is_enabled`
+    pub fn is_enabled(&self) -> bool {
+        self.node.is_enabled()
+    }
+
+    /// Frequency of the `time` CSR in Hz, from the node or `/cpus`
+    pub fn timebase_frequency(&self) -> Option<u64> {
+        let prop = self
+            .node
+            .get_property("timebase-frequency")
+            .or_else(|| self.cpus.get_property("timebase-frequency"))?;
+        frequency(prop)
+    }
+
+    /// Register width from `riscv,isa-base` or `riscv,isa`, 32 or 64
+    pub fn xlen(&self) -> Option<u32> {
+        let base = self
+            .string_property("riscv,isa-base")
+            .or_else(|| self.string_property("riscv,isa"))?;
+
+        match base.get(..4)? {
+            base if base.eq_ignore_ascii_case("rv32") => Some(32),
+            base if base.eq_ignore_ascii_case("rv64") => Some(64),
+            _ => None,
+        }
+    }
+
+    /// Name of every extension of the hart, without version. From `riscv,isa-extensions`, or
+    /// from the `riscv,isa` string for older trees.
+    pub fn extension_names(&self) -> impl Iterator<Item = &'a str> + use<'a> {
+        let list = self
+            .node
+            .get_property("riscv,isa-extensions")
+            .and_then(|p| p.as_str_list().ok());
+        let isa = match list {
+            Some(_) => "",
+            None => self.string_property("riscv,isa").unwrap_or_default(),
+        };
+
+        list.into_iter().flatten().chain(IsaStringNames::new(isa))
+    }
+
+    /// Known extensions of the hart, see `extension_names` for the others
+    pub fn extensions(&self) -> IsaExtensions {
+        self.extension_names()
+            .filter_map(IsaExtension::from_name)
+            .collect()
+    }
+
+    /// Whether the hart has the extension `name`, known by `IsaExtension` or not
+    pub fn has_extension(&self, name: &str) -> bool {
+        self.extension_names().any(|n| n.eq_ignore_ascii_case(name))
+    }
+
+    /// Translation scheme of the MMU, from `mmu-type`
+    pub fn mmu_type(&self) -> Option<MmuType> {
+        match self.string_property("mmu-type")? {
+            "riscv,none" => Some(MmuType::Bare),
+            "riscv,sv32" => Some(MmuType::Sv32),
+            "riscv,sv39" => Some(MmuType::Sv39),
+            "riscv,sv48" => Some(MmuType::Sv48),
+            "riscv,sv57" => Some(MmuType::Sv57),
+            _ => None,
+        }
+    }
+
+    /// Local interrupt controller of the hart, the child `riscv,cpu-intc` node
+    pub fn interrupt_controller(&self) -> Option<DeviceTreeNode<'a>> {
+        self.node
+            .children()
+            .find(|child| child.get_property("interrupt-controller").is_some())
+    }
+
+    /// Phandle used by the PLIC or the CLINT to reference the local interrupt controller
+    pub fn interrupt_controller_phandle(&self) -> Option<u32> {
+        self.interrupt_controller()?.phandle()
+    }
+
+    /// Position of the hart in `/cpus/cpu-map`, `None` when it is not in the map
+    pub fn position(&self) -> Option<CpuPosition> {
+        let phandle = self.node.phandle()?;
+        let map = self.cpus.get_child("cpu-map")?;
+
+        let (_, leaf) = map.descendants().find(|(_, node)| {
+            node.get_property("cpu")
+                .and_then(|p| p.as_u32().ok())
+                .is_some_and(|p| p == phandle)
+        })?;
+
+        let mut socket = None;
+        let mut cluster = None;
+        let mut core = None;
+        let mut thread = None;
+
+        for node in core::iter::once(leaf).chain(leaf.ancestors()) {
+            let name = node.name();
+            if name == "cpu-map" {
+                break;
+            }
+
+            // The innermost cluster is kept for nested clusters
+            let (slot, index) = if let Some(index) = name.strip_prefix("thread") {
+                (&mut thread, index)
+            } else if let Some(index) = name.strip_prefix("core") {
+                (&mut core, index)
+            } else if let Some(index) = name.strip_prefix("cluster") {
+                (&mut cluster, index)
+            } else if let Some(index) = name.strip_prefix("socket") {
+                (&mut socket, index)
+            } else {
+                continue;
+            };
+
+            if slot.is_none() {
+                *slot = index.parse().ok();
+            }
+        }
+
+        Some(CpuPosition {
+            socket,
+            cluster,
+            core: core?,
+            thread,
+        })
+    }
+
+    //
+    // NON-PUBLIC INTERFACE
+    //
+
+    fn string_property(&self, name: &str) -> Option<&'a str> {
+        self.node.get_property(name)?.value_str()
+    }
+}
+
+/// Translation scheme given by `mmu-type`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MmuType {
+    /// No translation, `riscv,none`
+    Bare,
+    Sv32,
+    Sv39,
+    Sv48,
+    Sv57,
+}
+
+/// Indexes of the `socketN`, `clusterN`, `coreN` and `threadN` nodes of `cpu-map` leading to a
+/// hart
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CpuPosition {
+    pub socket: Option<u32>,
+    pub cluster: Option<u32>,
+    pub core: u32,
+    /// Only for harts sharing a core
+    pub thread: Option<u32>,
+}
+
+/// Frequency on one or two cells
+fn frequency(prop: NodeProperty) -> Option<u64> {
+    match prop.raw_value().len() {
+        4 => prop.as_u32().ok().map(u64::from),
+        _ => prop.as_u64().ok(),
+    }
+}
@@ -0,0 +1,260 @@
+use core::fmt::{self, Display, Write};
+
+use crate::{Cells, DeviceTreeNode, DtbReader, NodeProperty};
+
+// Properties only made of phandles
+const REFERENCES: [&str; 7] = [
+    "interrupt-parent",
+    "memory-region",
+    "msi-parent",
+    "next-level-cache",
+    "phy-handle",
+    "regmap",
+    "cpu",
+];
+
+// Phandle lists, with the property of the referenced nodes giving their number of arguments
+const PHANDLE_LISTS: [(&str, &str); 10] = [
+    ("interrupts-extended", "#interrupt-cells"),
+    ("clocks", "#clock-cells"),
+    ("assigned-clocks", "#clock-cells"),
+    ("resets", "#reset-cells"),
+    ("dmas", "#dma-cells"),
+    ("phys", "#phy-cells"),
+    ("power-domains", "#power-domain-cells"),
+    ("mboxes", "#mbox-cells"),
+    ("pwms", "#pwm-cells"),
+    ("iommus", "#iommu-cells"),
+];
+
+impl<'a> DtbReader<'a> {
+    /// Writes the whole tree as a DTS file, with the memory reservation block
+    pub fn dump(&self, w: &mut impl Write) -> fmt::Result {
+        writeln!(w, "/dts-v1/;")?;
+        for entry in self.reserve_entry_iter() {
+            writeln!(
+                w,
+                "/memreserve/ {:#x} {:#x};",
+                entry.address(),
+                entry.size()
+            )?;
+        }
+        writeln!(w)?;
+
+        self.root_node().dump(self, w)
+    }
+}
+
+impl<'a> DeviceTreeNode<'a> {
+    /// Writes the node and its descendants as DTS source.
+    ///
+    /// The type of a value is guessed: printable strings, then cells when its length is a
+    /// multiple of 4, then bytes. The phandles of known properties like `clocks` are written as
+    /// references to the path of their node, like `<&{/soc/clock} 0x01>`, using `dtb`.
+    pub fn dump(&self, dtb: &DtbReader<'a>, w: &mut impl Write) -> fmt::Result {
+        self.dump_at(dtb, w, 0)
+    }
+
+    /// DTS source of the node and its descendants, see `dump`
+    pub fn dts<'r>(&self, dtb: &'r DtbReader<'a>) -> Dts<'a, 'r> {
+        Dts { node: *self, dtb }
+    }
+
+    //
+    // NON-PUBLIC INTERFACE
+    //
+
+    fn dump_at(&self, dtb: &DtbReader<'a>, w: &mut impl Write, depth: usize) -> fmt::Result {
+        let name = match self.full_name() {
+            "" => "/",
+            name => name,
+        };
+        indent(w, depth)?;
+        writeln!(w, "{name} {{")?;
+
+        let mut empty = true;
+        for prop in self.properties() {
+            indent(w, depth + 1)?;
+            write_property(w, dtb, &prop)?;
+            empty = false;
+        }
+
+        // Children are separated by an empty line
+        for child in self.children() {
+            if !empty {
+                writeln!(w)?;
+            }
+            child.dump_at(dtb, w, depth + 1)?;
+            empty = false;
+        }
+
+        indent(w, depth)?;
+        writeln!(w, "}};")
+    }
+}
+
+/// DTS source of a subtree, see `DeviceTreeNode::dts`
+#[derive(Clone, Copy)]
+pub struct Dts<'a, 'r> {
+    node: DeviceTreeNode<'a>,
+    dtb: &'r DtbReader<'a>,
+}
+
+impl Display for Dts<'_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.node.dump(self.dtb, f)
+    }
+}
+
+fn indent(w: &mut impl Write, depth: usize) -> fmt::Result {
+    (0..depth).try_for_each(|_| w.write_char('\t'))
+}
+
+fn write_property(w: &mut impl Write, dtb: &DtbReader, prop: &NodeProperty) -> fmt::Result {
+    let name = prop.name();
+    let value = prop.raw_value();
+
+    if value.is_empty() {
+        return writeln!(w, "{name};");
+    }
+    write!(w, "{name} = ")?;
+
+    let cells = Cells::new(value);
+    let references = cells.and_then(|cells| {
+        if REFERENCES.contains(&name) {
+            Some((cells, None))
+        } else if name == "gpios" || name.ends_with("-gpios") {
+            Some((cells, Some("#gpio-cells")))
+        } else {
+            let (_, cells_name) = PHANDLE_LISTS.iter().find(|(list, _)| *list == name)?;
+            Some((cells, Some(*cells_name)))
+        }
+    });
+
+    match (references, cells) {
+        (Some((cells, cells_name)), _) if is_phandle_list(dtb, cells, cells_name) => {
+            write_phandle_list(w, dtb, cells, cells_name)?;
+        }
+        _ if is_string_list(value) => write_string_list(w, value)?,
+        (_, Some(cells)) => write_cells(w, cells.iter())?,
+        (_, None) => write_bytes(w, value)?,
+    }
+
+    writeln!(w, ";")
+}
+
+/// Whether every byte is printable and the value is made of non-empty NUL terminated strings
+fn is_string_list(value: &[u8]) -> bool {
+    let printable = value
+        .iter()
+        .all(|&b| b == 0 || b == b' ' || b.is_ascii_graphic());
+
+    printable
+        && value.first() != Some(&0)
+        && value.last() == Some(&0)
+        && !value.windows(2).any(|pair| pair == [0, 0])
+}
+
+fn write_string_list(w: &mut impl Write, value: &[u8]) -> fmt::Result {
+    let strings = value[..value.len() - 1].split(|&b| b == 0);
+
+    for (i, string) in strings.enumerate() {
+        if i > 0 {
+            w.write_str(", ")?;
+        }
+
+        w.write_char('"')?;
+        for &b in string {
+            if b == b'"' || b == b'\\' {
+                w.write_char('\\')?;
+            }
+            w.write_char(b as char)?;
+        }
+        w.write_char('"')?;
+    }
+
+    Ok(())
+}
+
+fn write_cells(w: &mut impl Write, cells: impl Iterator<Item = u32>) -> fmt::Result {
+    w.write_char('<')?;
+    for (i, cell) in cells.enumerate() {
+        if i > 0 {
+            w.write_char(' ')?;
+        }
+        write!(w, "{cell:#04x}")?;
+    }
+    w.write_char('>')
+}
+
+fn write_bytes(w: &mut impl Write, value: &[u8]) -> fmt::Result {
+    w.write_char('[')?;
+    for (i, byte) in value.iter().enumerate() {
+        if i > 0 {
+            w.write_char(' ')?;
+        }
+        write!(w, "{byte:02x}")?;
+    }
+    w.write_char(']')
+}
+
+/// Number of arguments of the entry referencing `phandle`, `None` for an unknown phandle
+fn entry_arguments(dtb: &DtbReader, phandle: u32, cells_name: Option<&str>) -> Option<usize> {
+    // An empty entry has no arguments
+    if phandle == 0 {
+        return Some(0);
+    }
+
+    let node = dtb.node_by_phandle(phandle)?;
+    let count = cells_name
+        .and_then(|name| node.get_property(name))
+        .and_then(|prop| prop.as_u32().ok())
+        .unwrap_or(0);
+
+    Some(count as usize)
+}
+
+/// Whether every phandle is known and the last entry ends with the value
+fn is_phandle_list(dtb: &DtbReader, cells: Cells, cells_name: Option<&str>) -> bool {
+    let mut i = 0;
+    while let Some(phandle) = cells.get(i) {
+        let Some(count) = entry_arguments(dtb, phandle, cells_name) else {
+            return false;
+        };
+        i += 1 + count;
+    }
+
+    i == cells.len()
+}
+
+/// Writes every entry as `<&{/path} arguments>`, the list must be checked by `is_phandle_list`
+fn write_phandle_list(
+    w: &mut impl Write,
+    dtb: &DtbReader,
+    cells: Cells,
+    cells_name: Option<&str>,
+) -> fmt::Result {
+    let mut rest = cells.iter();
+    let mut first = true;
+
+    while let Some(phandle) = rest.next() {
+        if !first {
+            w.write_str(", ")?;
+        }
+        first = false;
+
+        let count = entry_arguments(dtb, phandle, cells_name).unwrap_or(0);
+        let Some(node) = dtb.node_by_phandle(phandle) else {
+            write_cells(w, [phandle].into_iter())?;
+            continue;
+        };
+
+        write!(w, "<&{{{}}}", node.path())?;
+        for argument in rest.by_ref().take(count) {
+            write!(w, " {argument:#04x}")?;
+        }
+        w.write_char('>')?;
+    }
+
+    Ok(())
+}
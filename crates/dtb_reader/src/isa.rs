@@ -0,0 +1,308 @@
+use core::fmt::{self, Display};
+
+/// Extensions of the RISC-V ISA known by `IsaExtensions`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum IsaExtension {
+    I,
+    M,
+    A,
+    F,
+    D,
+    Q,
+    C,
+    V,
+    H,
+    Zicsr,
+    Zifencei,
+    Zicntr,
+    Zihpm,
+    Zicbom,
+    Zicbop,
+    Zicboz,
+    Zicond,
+    Zihintntl,
+    Zihintpause,
+    Zawrs,
+    Zacas,
+    Zfh,
+    Zfa,
+    Zca,
+    Zcb,
+    Zcd,
+    Zba,
+    Zbb,
+    Zbc,
+    Zbs,
+    Zbkb,
+    Zkr,
+    Zkt,
+    Smaia,
+    Ssaia,
+    Sscofpmf,
+    Sstc,
+    Svadu,
+    Svinval,
+    Svnapot,
+    Svpbmt,
+}
+
+impl IsaExtension {
+    pub const ALL: [IsaExtension; 41] = [
+        IsaExtension::I,
+        IsaExtension::M,
+        IsaExtension::A,
+        IsaExtension::F,
+        IsaExtension::D,
+        IsaExtension::Q,
+        IsaExtension::C,
+        IsaExtension::V,
+        IsaExtension::H,
+        IsaExtension::Zicsr,
+        IsaExtension::Zifencei,
+        IsaExtension::Zicntr,
+        IsaExtension::Zihpm,
+        IsaExtension::Zicbom,
+        IsaExtension::Zicbop,
+        IsaExtension::Zicboz,
+        IsaExtension::Zicond,
+        IsaExtension::Zihintntl,
+        IsaExtension::Zihintpause,
+        IsaExtension::Zawrs,
+        IsaExtension::Zacas,
+        IsaExtension::Zfh,
+        IsaExtension::Zfa,
+        IsaExtension::Zca,
+        IsaExtension::Zcb,
+        IsaExtension::Zcd,
+        IsaExtension::Zba,
+        IsaExtension::Zbb,
+        IsaExtension::Zbc,
+        IsaExtension::Zbs,
+        IsaExtension::Zbkb,
+        IsaExtension::Zkr,
+        IsaExtension::Zkt,
+        IsaExtension::Smaia,
+        IsaExtension::Ssaia,
+        IsaExtension::Sscofpmf,
+        IsaExtension::Sstc,
+        IsaExtension::Svadu,
+        IsaExtension::Svinval,
+        IsaExtension::Svnapot,
+        IsaExtension::Svpbmt,
+    ];
+
+    /// Name used in ISA strings, like `zicsr`
+    pub fn name(self) -> &'static str {
+        match self {
+            IsaExtension::I => "i",
+            IsaExtension::M => "m",
+            IsaExtension::A => "a",
+            IsaExtension::F => "f",
+            IsaExtension::D => "d",
+            IsaExtension::Q => "q",
+            IsaExtension::C => "c",
+            IsaExtension::V => "v",
+            IsaExtension::H => "h",
+            IsaExtension::Zicsr => "zicsr",
+            IsaExtension::Zifencei => "zifencei",
+            IsaExtension::Zicntr => "zicntr",
+            IsaExtension::Zihpm => "zihpm",
+            IsaExtension::Zicbom => "zicbom",
+            IsaExtension::Zicbop => "zicbop",
+            IsaExtension::Zicboz => "zicboz",
+            IsaExtension::Zicond => "zicond",
+            IsaExtension::Zihintntl => "zihintntl",
+            IsaExtension::Zihintpause => "zihintpause",
+            IsaExtension::Zawrs => "zawrs",
+            IsaExtension::Zacas => "zacas",
+            IsaExtension::Zfh => "zfh",
+            IsaExtension::Zfa => "zfa",
+            IsaExtension::Zca => "zca",
+            IsaExtension::Zcb => "zcb",
+            IsaExtension::Zcd => "zcd",
+            IsaExtension::Zba => "zba",
+            IsaExtension::Zbb => "zbb",
+            IsaExtension::Zbc => "zbc",
+            IsaExtension::Zbs => "zbs",
+            IsaExtension::Zbkb => "zbkb",
+            IsaExtension::Zkr => "zkr",
+            IsaExtension::Zkt => "zkt",
+            IsaExtension::Smaia => "smaia",
+            IsaExtension::Ssaia => "ssaia",
+            IsaExtension::Sscofpmf => "sscofpmf",
+            IsaExtension::Sstc => "sstc",
+            IsaExtension::Svadu => "svadu",
+            IsaExtension::Svinval => "svinval",
+            IsaExtension::Svnapot => "svnapot",
+            IsaExtension::Svpbmt => "svpbmt",
+        }
+    }
+
+    /// Extension with this name, ignoring case
+    pub fn from_name(name: &str) -> Option<IsaExtension> {
+        IsaExtension::ALL
+            .into_iter()
+            .find(|e| e.name().eq_ignore_ascii_case(name))
+    }
+}
+
+/// Set of `IsaExtension`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct IsaExtensions(u64);
+
+impl IsaExtensions {
+    pub fn new() -> IsaExtensions {
+        IsaExtensions::default()
+    }
+
+    pub fn insert(&mut self, extension: IsaExtension) {
+        self.0 |= 1 << extension as u8;
+    }
+
+    pub fn contains(&self, extension: IsaExtension) -> bool {
+        self.0 & (1 << extension as u8) != 0
+    }
+
+    /// Whether every extension of `other` is in this set
+    pub fn contains_all(&self, other: IsaExtensions) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Extensions in the canonical order of ISA strings
+    pub fn iter(&self) -> impl Iterator<Item = IsaExtension> + use<> {
+        let set = *self;
+        IsaExtension::ALL
+            .into_iter()
+            .filter(move |e| set.contains(*e))
+    }
+}
+
+impl FromIterator<IsaExtension> for IsaExtensions {
+    fn from_iter<T: IntoIterator<Item = IsaExtension>>(iter: T) -> Self {
+        let mut set = IsaExtensions::new();
+        for extension in iter {
+            set.insert(extension);
+        }
+        set
+    }
+}
+
+/// ISA string of the set, like `imac_zicsr_zifencei`
+impl Display for IsaExtensions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Single-letter extensions come first and are not separated
+        for (i, extension) in self.iter().enumerate() {
+            if i > 0 && extension.name().len() > 1 {
+                f.write_str("_")?;
+            }
+            f.write_str(extension.name())?;
+        }
+        Ok(())
+    }
+}
+
+/// Names of the extensions of a `riscv,isa` string like `rv64imafdc_zicsr_zifencei`, without
+/// their version. `g` is expanded to the extensions it stands for.
+#[derive(Debug, Clone)]
+pub(crate) struct IsaStringNames<'a> {
+    rest: &'a str,
+    // Still in the single-letter extensions following the base
+    single_letter: bool,
+    // Extensions of `g` not returned yet
+    expanded: &'static [&'static str],
+}
+
+// Extensions `g` stands for, after `i`
+const G_EXTENSIONS: &[&str] = &["m", "a", "f", "d", "zicsr", "zifencei"];
+
+impl<'a> IsaStringNames<'a> {
+    pub(crate) fn new(isa: &'a str) -> IsaStringNames<'a> {
+        let rest = isa
+            .get(..4)
+            .filter(|base| base.eq_ignore_ascii_case("rv32") || base.eq_ignore_ascii_case("rv64"))
+            .map_or(isa, |_| &isa[4..]);
+
+        IsaStringNames {
+            rest,
+            single_letter: true,
+            expanded: &[],
+        }
+    }
+}
+
+impl<'a> Iterator for IsaStringNames<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some((name, rest)) = self.expanded.split_first() {
+            self.expanded = rest;
+            return Some(name);
+        }
+
+        loop {
+            let first = self.rest.chars().next()?;
+
+            if first == '_' {
+                self.rest = &self.rest[1..];
+                self.single_letter = false;
+                continue;
+            }
+
+            // Multi-letter extensions start with `z`, `s` or `x`, even without `_` before them
+            if self.single_letter && !matches!(first.to_ascii_lowercase(), 'z' | 's' | 'x') {
+                let name = &self.rest[..first.len_utf8()];
+                self.rest = skip_version(&self.rest[name.len()..]);
+
+                if name.eq_ignore_ascii_case("g") {
+                    self.expanded = G_EXTENSIONS;
+                    return Some("i");
+                }
+                return Some(name);
+            }
+
+            self.single_letter = false;
+            let end = self.rest.find('_').unwrap_or(self.rest.len());
+            let name = strip_version(&self.rest[..end]);
+            self.rest = &self.rest[end..];
+
+            if !name.is_empty() {
+                return Some(name);
+            }
+        }
+    }
+}
+
+/// Skips the version after a single-letter extension, like `2p1`
+fn skip_version(rest: &str) -> &str {
+    let rest = rest.trim_start_matches(|c: char| c.is_ascii_digit());
+    match rest.strip_prefix('p') {
+        Some(minor) if minor.starts_with(|c: char| c.is_ascii_digit()) => {
+            minor.trim_start_matches(|c: char| c.is_ascii_digit())
+        }
+        _ => rest,
+    }
+}
+
+/// Removes the version at the end of a multi-letter extension, like `zicsr2p0`
+fn strip_version(name: &str) -> &str {
+    let without_digits = name.trim_end_matches(|c: char| c.is_ascii_digit());
+    if without_digits.len() == name.len() {
+        return name;
+    }
+
+    match without_digits.strip_suffix('p') {
+        Some(major) if major.ends_with(|c: char| c.is_ascii_digit()) => {
+            major.trim_end_matches(|c: char| c.is_ascii_digit())
+        }
+        _ => without_digits,
+    }
+}
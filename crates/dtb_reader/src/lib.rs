@@ -1,8 +1,33 @@
 #![no_std]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[cfg(feature = "alloc")]
+mod builder;
+mod cpus;
+mod dts;
+mod isa;
+#[cfg(feature = "alloc")]
+mod overlay;
+mod phandle_index;
 mod reader;
 mod reserve_entry;
+mod search;
 mod tree;
 
-pub use reader::DtbReader;
-pub use tree::DeviceTreeNode;
+#[cfg(feature = "alloc")]
+pub use builder::{BuildError, FdtBuilder};
+pub use cpus::{CpuInfo, CpuPosition, MmuType};
+pub use dts::Dts;
+pub use isa::{IsaExtension, IsaExtensions};
+#[cfg(feature = "alloc")]
+pub use overlay::{Overlay, OverlayError, apply_overlay};
+pub use phandle_index::PhandleIndex;
+pub use reader::{DtbInitError, DtbReader};
+pub use reserve_entry::MemoryReserveEntry;
+pub use search::FindCompatible;
+pub use tree::{
+    Ancestors, Cells, CellsIter, Descendants, DeviceTreeNode, MAX_DEPTH, NodePath, NodeProperty,
+    ParsingError, ParsingErrorKind, PhandleList, PropertyError, Range,
+};
@@ -0,0 +1,431 @@
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt::{self, Display};
+
+use crate::{
+    BuildError, DeviceTreeNode, DtbInitError, DtbReader, FdtBuilder,
+    reader::{blob_at, parse_blob},
+};
+
+/// Device tree overlay (`.dtbo`), a blob made of fragments to apply on a base tree
+pub struct Overlay<'a> {
+    blob: &'a [u8],
+    root_node: DeviceTreeNode<'a>,
+}
+
+impl Overlay<'static> {
+    /// Initializes an overlay from a raw pointer to its blob
+    ///
+    /// # Errors
+    ///
+    /// Returns an error on invalid or unsupported blob.
+    ///
+    /// # Safety
+    ///
+    /// **Unsafe**. Caller must ensure `ptr` points to a valid blob of `totalsize` bytes that is
+    /// never modified.
+    pub unsafe fn new(ptr: *const u32) -> Result<Overlay<'static>, DtbInitError> {
+        Overlay::from_bytes(unsafe { blob_at(ptr)? })
+    }
+}
+
+impl<'a> Overlay<'a> {
+    /// Initializes an overlay from a buffer, with the same checks as `DtbReader::from_bytes`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error on invalid or unsupported blob.
+    pub fn from_bytes(bytes: &'a [u8]) -> Result<Overlay<'a>, DtbInitError> {
+        let (blob, _, root_node) = parse_blob(bytes)?;
+        Ok(Overlay { blob, root_node })
+    }
+
+    /// The whole blob, `totalsize` bytes long
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.blob
+    }
+
+    pub fn root_node(&self) -> DeviceTreeNode<'a> {
+        self.root_node
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OverlayError {
+    /// A label of `__fixups__` is not in the `__symbols__` of the base tree
+    UnknownSymbol(String),
+    /// A `__fixups__` entry is malformed or points outside of the overlay
+    InvalidFixup(String),
+    /// A `__local_fixups__` entry does not match the overlay, the path of the entry
+    InvalidLocalFixup(String),
+    /// The `target` or `target-path` of a fragment is missing or not in the base tree
+    TargetNotFound(String),
+    Build(BuildError),
+}
+
+impl Display for OverlayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OverlayError::UnknownSymbol(label) => write!(f, "unknown symbol '{label}'"),
+            OverlayError::InvalidFixup(fixup) => write!(f, "invalid fixup '{fixup}'"),
+            OverlayError::InvalidLocalFixup(path) => write!(f, "invalid local fixup at '{path}'"),
+            OverlayError::TargetNotFound(fragment) => {
+                write!(f, "target of '{fragment}' not found")
+            }
+            OverlayError::Build(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl From<BuildError> for OverlayError {
+    fn from(value: BuildError) -> Self {
+        Self::Build(value)
+    }
+}
+
+/// Applies `overlay` on `base` and returns the merged blob.
+///
+/// Like `fdt_overlay_apply` of libfdt:
+/// - the phandles of the overlay are moved past the ones of the base tree, `__local_fixups__`
+///   locates the references to update
+/// - `__fixups__` references to labels of the base are resolved with its `__symbols__`
+/// - every `fragment@N/__overlay__` is merged in the node pointed by the `target` phandle or
+///   the `target-path` of the fragment
+/// - the `__symbols__` of the overlay are added to the ones of the base, with their path in the
+///   merged tree
+///
+/// # Errors
+///
+/// Returns the first reference that cannot be resolved, the base tree is never modified.
+pub fn apply_overlay(base: &DtbReader, overlay: &Overlay) -> Result<Vec<u8>, OverlayError> {
+    let mut tree = Node::from_node(&base.root_node());
+    let mut overlay_tree = Node::from_node(&overlay.root_node());
+
+    let delta = tree.max_phandle();
+    overlay_tree.for_each_mut(&mut |node| {
+        for name in ["phandle", "linux,phandle"] {
+            if let Some(value) = node.property_mut(name) {
+                add_to_cell(value, 0, delta);
+            }
+        }
+    });
+    let mut next_phandle = overlay_tree.max_phandle().max(delta) + 1;
+
+    if let Some(local_fixups) = overlay_tree.take_child("__local_fixups__") {
+        apply_local_fixups(&mut overlay_tree, &local_fixups, delta, &mut String::new())?;
+    }
+
+    if let Some(fixups) = overlay_tree.take_child("__fixups__") {
+        for (label, references) in &fixups.properties {
+            let path = tree
+                .child("__symbols__")
+                .and_then(|symbols| symbols.property_str(label))
+                .ok_or_else(|| OverlayError::UnknownSymbol(label.clone()))?
+                .to_string();
+            let target = tree
+                .find_mut(&path)
+                .ok_or_else(|| OverlayError::UnknownSymbol(label.clone()))?;
+            let phandle = target.phandle_or_assign(&mut next_phandle);
+
+            for reference in references.split(|&b| b == 0).filter(|r| !r.is_empty()) {
+                apply_fixup(&mut overlay_tree, reference, phandle)?;
+            }
+        }
+    }
+
+    let symbols = overlay_tree.take_child("__symbols__");
+
+    // Path of the target of every fragment
+    let mut targets = Vec::new();
+    for fragment in &overlay_tree.children {
+        let Some(content) = fragment.child("__overlay__") else {
+            continue;
+        };
+        let not_found = || OverlayError::TargetNotFound(fragment.name.clone());
+
+        let target_path = if let Some(phandle) = fragment.property_u32("target") {
+            tree.path_of_phandle(phandle).ok_or_else(not_found)?
+        } else {
+            fragment
+                .property_str("target-path")
+                .ok_or_else(not_found)?
+                .to_string()
+        };
+
+        tree.find_mut(&target_path)
+            .ok_or_else(not_found)?
+            .merge(content);
+        targets.push((format!("/{}/__overlay__", fragment.name), target_path));
+    }
+
+    if let Some(symbols) = symbols {
+        let merged_symbols = tree.child_or_insert("__symbols__");
+
+        for (label, value) in &symbols.properties {
+            let path = symbols.property_str(label).unwrap_or_default();
+            let merged_path = targets.iter().find_map(|(fragment, target)| {
+                let rest = path.strip_prefix(fragment.as_str())?;
+                match (target.as_str(), rest) {
+                    (target, "") => Some(target.to_string()),
+                    ("/", rest) => Some(rest.to_string()),
+                    (target, rest) => Some(format!("{target}{rest}")),
+                }
+            });
+
+            match merged_path {
+                Some(path) => merged_symbols.set_property(label, &c_string(&path)),
+                // A label outside of the fragments, like on a fragment itself
+                None => merged_symbols.set_property(label, value),
+            }
+        }
+    }
+
+    let mut builder = FdtBuilder::new();
+    builder.boot_cpuid_phys(base.fdt_header.boot_cpuid_phys());
+    for entry in base.reserve_entry_iter() {
+        builder.reserve_memory(entry.address(), entry.size());
+    }
+    tree.write(&mut builder);
+
+    Ok(builder.finish()?)
+}
+
+/// Adds `delta` to every cell listed in `fixups`, which mirrors the nodes of `node`
+fn apply_local_fixups(
+    node: &mut Node,
+    fixups: &Node,
+    delta: u32,
+    path: &mut String,
+) -> Result<(), OverlayError> {
+    for (name, offsets) in &fixups.properties {
+        let invalid = || OverlayError::InvalidLocalFixup(format!("{path}/{name}"));
+        let value = node.property_mut(name).ok_or_else(invalid)?;
+
+        for offset in offsets.chunks_exact(4) {
+            let offset = u32::from_be_bytes(offset.try_into().unwrap()) as usize;
+            if !add_to_cell(value, offset, delta) {
+                return Err(invalid());
+            }
+        }
+    }
+
+    for fixups_child in &fixups.children {
+        let len = path.len();
+        path.push('/');
+        path.push_str(&fixups_child.name);
+
+        let child = node
+            .child_mut(&fixups_child.name)
+            .ok_or_else(|| OverlayError::InvalidLocalFixup(path.clone()))?;
+        apply_local_fixups(child, fixups_child, delta, path)?;
+
+        path.truncate(len);
+    }
+
+    Ok(())
+}
+
+/// Writes `phandle` at the place given by a `path:property:offset` reference
+fn apply_fixup(overlay: &mut Node, reference: &[u8], phandle: u32) -> Result<(), OverlayError> {
+    let reference = core::str::from_utf8(reference).unwrap_or_default();
+    let invalid = || OverlayError::InvalidFixup(reference.to_string());
+
+    // The path may contain ':' in unit addresses, the two other fields cannot
+    let mut fields = reference.rsplitn(3, ':');
+    let (Some(offset), Some(property), Some(path)) = (fields.next(), fields.next(), fields.next())
+    else {
+        return Err(invalid());
+    };
+    let offset: usize = offset.parse().map_err(|_| invalid())?;
+
+    let value = overlay
+        .find_mut(path)
+        .and_then(|node| node.property_mut(property))
+        .ok_or_else(invalid)?;
+    let cell = value.get_mut(offset..offset + 4).ok_or_else(invalid)?;
+    cell.copy_from_slice(&phandle.to_be_bytes());
+
+    Ok(())
+}
+
+/// Adds `delta` to the cell at `offset`, `false` if it is out of bounds
+fn add_to_cell(value: &mut [u8], offset: usize, delta: u32) -> bool {
+    let Some(cell) = value.get_mut(offset..offset + 4) else {
+        return false;
+    };
+
+    let old = u32::from_be_bytes((&*cell).try_into().unwrap());
+    cell.copy_from_slice(&old.wrapping_add(delta).to_be_bytes());
+    true
+}
+
+fn c_string(string: &str) -> Vec<u8> {
+    let mut bytes = Vec::from(string.as_bytes());
+    bytes.push(0);
+    bytes
+}
+
+/// Node that can be modified, the overlay is applied on a copy of the base tree
+#[derive(Debug, Clone)]
+struct Node {
+    name: String,
+    properties: Vec<(String, Vec<u8>)>,
+    children: Vec<Node>,
+}
+
+impl Node {
+    fn from_node(node: &DeviceTreeNode) -> Node {
+        Node {
+            name: node.full_name().to_string(),
+            properties: node
+                .properties()
+                .map(|p| (p.name().to_string(), p.raw_value().to_vec()))
+                .collect(),
+            children: node.children().map(|c| Node::from_node(&c)).collect(),
+        }
+    }
+
+    fn write(&self, builder: &mut FdtBuilder) {
+        builder.begin_node(&self.name);
+        for (name, value) in &self.properties {
+            builder.property(name, value);
+        }
+        for child in &self.children {
+            child.write(builder);
+        }
+        builder.end_node();
+    }
+
+    fn property(&self, name: &str) -> Option<&[u8]> {
+        self.properties
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, value)| value.as_slice())
+    }
+
+    fn property_mut(&mut self, name: &str) -> Option<&mut Vec<u8>> {
+        self.properties
+            .iter_mut()
+            .find(|(n, _)| n == name)
+            .map(|(_, value)| value)
+    }
+
+    fn property_u32(&self, name: &str) -> Option<u32> {
+        Some(u32::from_be_bytes(self.property(name)?.try_into().ok()?))
+    }
+
+    fn property_str(&self, name: &str) -> Option<&str> {
+        let (&0, string) = self.property(name)?.split_last()? else {
+            return None;
+        };
+        core::str::from_utf8(string).ok()
+    }
+
+    fn set_property(&mut self, name: &str, value: &[u8]) {
+        match self.property_mut(name) {
+            Some(old) => *old = value.to_vec(),
+            None => self.properties.push((name.to_string(), value.to_vec())),
+        }
+    }
+
+    fn phandle(&self) -> Option<u32> {
+        self.property_u32("phandle")
+            .or_else(|| self.property_u32("linux,phandle"))
+    }
+
+    fn phandle_or_assign(&mut self, next_phandle: &mut u32) -> u32 {
+        if let Some(phandle) = self.phandle() {
+            return phandle;
+        }
+
+        let phandle = *next_phandle;
+        *next_phandle += 1;
+        self.set_property("phandle", &phandle.to_be_bytes());
+        phandle
+    }
+
+    fn max_phandle(&self) -> u32 {
+        let children = self.children.iter().map(Node::max_phandle);
+        children.fold(self.phandle().unwrap_or(0), u32::max)
+    }
+
+    fn for_each_mut(&mut self, f: &mut impl FnMut(&mut Node)) {
+        f(self);
+        for child in &mut self.children {
+            child.for_each_mut(f);
+        }
+    }
+
+    fn child(&self, name: &str) -> Option<&Node> {
+        self.children.iter().find(|c| c.name == name)
+    }
+
+    fn child_mut(&mut self, name: &str) -> Option<&mut Node> {
+        self.children.iter_mut().find(|c| c.name == name)
+    }
+
+    fn child_or_insert(&mut self, name: &str) -> &mut Node {
+        let index = match self.children.iter().position(|c| c.name == name) {
+            Some(index) => index,
+            None => {
+                self.children.push(Node {
+                    name: name.to_string(),
+                    properties: Vec::new(),
+                    children: Vec::new(),
+                });
+                self.children.len() - 1
+            }
+        };
+
+        &mut self.children[index]
+    }
+
+    fn take_child(&mut self, name: &str) -> Option<Node> {
+        let index = self.children.iter().position(|c| c.name == name)?;
+        Some(self.children.remove(index))
+    }
+
+    /// Node at an absolute `path`, a component without unit address matches the first node with
+    /// that name like `DtbReader::find_node`
+    fn find_mut(&mut self, path: &str) -> Option<&mut Node> {
+        let mut current = self;
+
+        for part in path.split('/').filter(|p| !p.is_empty()) {
+            let index = current.children.iter().position(|c| {
+                c.name == part || (!part.contains('@') && c.name.split('@').next() == Some(part))
+            })?;
+            current = &mut current.children[index];
+        }
+
+        Some(current)
+    }
+
+    fn path_of_phandle(&self, phandle: u32) -> Option<String> {
+        if self.phandle() == Some(phandle) {
+            return Some(String::from("/"));
+        }
+
+        self.children.iter().find_map(|child| {
+            let path = child.path_of_phandle(phandle)?;
+            Some(match path.as_str() {
+                "/" => format!("/{}", child.name),
+                rest => format!("/{}{rest}", child.name),
+            })
+        })
+    }
+
+    /// Adds the properties of `overlay`, replacing the ones with the same name, then merges its
+    /// children with the ones that have the same name
+    fn merge(&mut self, overlay: &Node) {
+        for (name, value) in &overlay.properties {
+            self.set_property(name, value);
+        }
+
+        for child in &overlay.children {
+            self.child_or_insert(&child.name).merge(child);
+        }
+    }
+}
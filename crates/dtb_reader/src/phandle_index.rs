@@ -0,0 +1,66 @@
+use crate::DtbReader;
+
+// Enough for most boards, the nodes past it are found by walking the tree
+const CAPACITY: usize = 256;
+
+/// Offsets of the nodes with a phandle, sorted by phandle.
+///
+/// Built once and given to the reader with [`DtbReader::with_phandle_index`], the reader itself
+/// stays small enough to be moved around.
+pub struct PhandleIndex {
+    entries: [(u32, u32); CAPACITY],
+    len: usize,
+    // `false` when some phandles did not fit
+    complete: bool,
+    // Address of the indexed blob
+    blob: usize,
+}
+
+impl PhandleIndex {
+    pub fn new(dtb: &DtbReader) -> PhandleIndex {
+        let root = dtb.root_node();
+        let mut index = PhandleIndex {
+            entries: [(0, 0); CAPACITY],
+            len: 0,
+            complete: true,
+            blob: dtb.as_bytes().as_ptr() as usize,
+        };
+
+        let nodes = core::iter::once(root).chain(root.descendants().map(|(_, node)| node));
+        for node in nodes {
+            let Some(phandle) = node.phandle() else {
+                continue;
+            };
+
+            if index.len == CAPACITY {
+                index.complete = false;
+                break;
+            }
+            // Offsets fit in 32 bits like `totalsize`
+            index.entries[index.len] = (phandle, node.offset() as u32);
+            index.len += 1;
+        }
+
+        index.entries[..index.len].sort_unstable_by_key(|&(phandle, _)| phandle);
+        index
+    }
+
+    /// Offset of the node with `phandle` in the structure block
+    pub(crate) fn get(&self, phandle: u32) -> Option<usize> {
+        let entries = &self.entries[..self.len];
+        let position = entries
+            .binary_search_by_key(&phandle, |&(phandle, _)| phandle)
+            .ok()?;
+
+        Some(entries[position].1 as usize)
+    }
+
+    pub(crate) fn is_complete(&self) -> bool {
+        self.complete
+    }
+
+    /// Whether the index was built for the blob at `blob`
+    pub(crate) fn indexes(&self, blob: &[u8]) -> bool {
+        self.blob == blob.as_ptr() as usize
+    }
+}
@@ -1,14 +1,16 @@
 use crate::{
     DeviceTreeNode,
+    phandle_index::PhandleIndex,
     reserve_entry::{MemoryReserveEntry, MemoryReserveEntryIter},
-    tree::ParsingError,
+    tree::{Blocks, ParsingError},
 };
 
-const DTB_VERSION: u32 = 17;
-const MAGIC_VALUE: u32 = 0xd00dfeed;
+pub(crate) const DTB_VERSION: u32 = 17;
+pub(crate) const MAGIC_VALUE: u32 = 0xd00dfeed;
 
-#[repr(C)]
-#[derive(Debug)]
+pub(crate) const HEADER_SIZE: usize = core::mem::size_of::<Header>();
+
+#[derive(Debug, Clone, Copy)]
 pub struct Header {
     magic: u32,
     totalsize: u32,
@@ -22,23 +24,63 @@ pub struct Header {
     size_dt_struct: u32,
 }
 
+impl Header {
+    /// Physical id of the boot hart
+    pub fn boot_cpuid_phys(&self) -> u32 {
+        self.boot_cpuid_phys
+    }
+
+    /// Reads the Big-Endian header at the start of `bytes`, which must hold at least `HEADER_SIZE` bytes
+    fn parse(bytes: &[u8]) -> Header {
+        let field = |index: usize| {
+            let offset = index * 4;
+            u32::from_be_bytes(bytes[offset..offset + 4].try_into().unwrap())
+        };
+
+        Header {
+            magic: field(0),
+            totalsize: field(1),
+            off_dt_struct: field(2),
+            off_dt_strings: field(3),
+            off_mem_rsvmap: field(4),
+            version: field(5),
+            last_comp_version: field(6),
+            boot_cpuid_phys: field(7),
+            size_dt_strings: field(8),
+            size_dt_struct: field(9),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum DtbInitError {
     UnsupportedDtbVersion,
-    InvalidHeader { expected: u32, found: u32 },
+    InvalidHeader {
+        expected: u32,
+        found: u32,
+    },
+    /// The buffer is smaller than the header or than the `totalsize` of the header
+    Truncated {
+        expected: usize,
+        found: usize,
+    },
+    /// A block does not fit between the header and `totalsize`
+    BlockOutOfBounds,
+    MisalignedBlock,
     NoCpusNode,
     ParsingError(ParsingError),
 }
 
-pub struct DtbReader {
-    ptr: *const u32,
+pub struct DtbReader<'a> {
+    blob: &'a [u8],
     pub fdt_header: Header,
-    root_node: DeviceTreeNode,
-    cpus_node: DeviceTreeNode,
-    aliases_node: Option<DeviceTreeNode>,
+    root_node: DeviceTreeNode<'a>,
+    cpus_node: DeviceTreeNode<'a>,
+    aliases_node: Option<DeviceTreeNode<'a>>,
+    phandles: Option<&'a PhandleIndex>,
 }
 
-impl DtbReader {
+impl DtbReader<'static> {
     /// Initializes a new DtbReader from a raw pointer to a Device Tree Blob (DTB).
     ///
     /// # Errors
@@ -47,40 +89,26 @@ impl DtbReader {
     ///
     /// # Safety
     ///
-    /// **Unsafe**. Caller must ensure `ptr` points to a valid DTB memory region for the lifetime of `DtbReader`.
+    /// **Unsafe**. Caller must ensure `ptr` points to a valid DTB memory region of `totalsize` bytes
+    /// that is never modified.
     ///
-    pub unsafe fn new(ptr: *const u32) -> Result<DtbReader, DtbInitError> {
-        let be_header = unsafe { &*(ptr as *const Header) };
-
-        // Header is Big-Endian by default so we need to convert it
-        let header = Header {
-            magic: u32::from_be(be_header.magic),
-            totalsize: u32::from_be(be_header.totalsize),
-            off_dt_struct: u32::from_be(be_header.off_dt_struct),
-            off_dt_strings: u32::from_be(be_header.off_dt_strings),
-            off_mem_rsvmap: u32::from_be(be_header.off_mem_rsvmap),
-            version: u32::from_be(be_header.version),
-            last_comp_version: u32::from_be(be_header.last_comp_version),
-            boot_cpuid_phys: u32::from_be(be_header.boot_cpuid_phys),
-            size_dt_strings: u32::from_be(be_header.size_dt_strings),
-            size_dt_struct: u32::from_be(be_header.size_dt_struct),
-        };
-
-        if header.version != DTB_VERSION && header.last_comp_version != DTB_VERSION {
-            return Err(DtbInitError::UnsupportedDtbVersion);
-        }
-
-        if header.magic != MAGIC_VALUE {
-            return Err(DtbInitError::InvalidHeader {
-                expected: MAGIC_VALUE,
-                found: header.magic,
-            });
-        }
+    pub unsafe fn new(ptr: *const u32) -> Result<DtbReader<'static>, DtbInitError> {
+        DtbReader::from_bytes(unsafe { blob_at(ptr)? })
+    }
+}
 
-        let str_block_ptr = unsafe { ptr.byte_offset(header.off_dt_strings as isize) as *const u8 };
-        let root_node_ptr = unsafe { ptr.byte_offset(header.off_dt_struct as isize) };
-        let root_node = DeviceTreeNode::parse(root_node_ptr, str_block_ptr)
-            .map_err(DtbInitError::ParsingError)?;
+impl<'a> DtbReader<'a> {
+    /// Initializes a new DtbReader from a buffer holding a Device Tree Blob (DTB).
+    ///
+    /// The header, the position of the blocks and their alignment are validated first,
+    /// parsing never reads outside of the blocks. Every node and property is then checked, so the
+    /// infallible iterators of the nodes never stop early.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error on invalid or unsupported DTB.
+    pub fn from_bytes(bytes: &'a [u8]) -> Result<DtbReader<'a>, DtbInitError> {
+        let (blob, header, root_node) = parse_blob(bytes)?;
 
         let mut cpus_node = Err(DtbInitError::NoCpusNode);
         let mut aliases_node = None;
@@ -94,31 +122,75 @@ impl DtbReader {
         }
 
         Ok(DtbReader {
-            ptr,
+            blob,
             fdt_header: header,
             root_node,
             cpus_node: cpus_node?,
             aliases_node,
+            phandles: None,
         })
     }
 
-    pub fn reserve_entry_iter(&self) -> impl Iterator<Item = MemoryReserveEntry> {
-        let start_ptr = unsafe {
-            self.ptr
-                .byte_offset(self.fdt_header.off_mem_rsvmap as isize)
-        };
-        MemoryReserveEntryIter::new(start_ptr)
+    pub fn reserve_entry_iter(&self) -> impl Iterator<Item = MemoryReserveEntry> + use<'a> {
+        MemoryReserveEntryIter::new(&self.blob[self.fdt_header.off_mem_rsvmap as usize..])
+    }
+
+    /// The whole blob, `totalsize` bytes long
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.blob
+    }
+
+    /// Size in bytes of the whole blob
+    pub fn total_size(&self) -> usize {
+        self.fdt_header.totalsize as usize
     }
 
-    pub fn root_node(&self) -> DeviceTreeNode {
+    pub fn root_node(&self) -> DeviceTreeNode<'a> {
         self.root_node
     }
 
-    pub fn cpus_node(&self) -> DeviceTreeNode {
+    pub fn cpus_node(&self) -> DeviceTreeNode<'a> {
         self.cpus_node
     }
 
-    pub fn resolve_alias(&self, alias: &str) -> Option<&str> {
+    /// Looks nodes up by phandle in `index` instead of walking the tree
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` was built for another blob.
+    pub fn with_phandle_index(self, index: &'a PhandleIndex) -> DtbReader<'a> {
+        assert!(index.indexes(self.blob), "phandle index of another blob");
+
+        DtbReader {
+            phandles: Some(index),
+            ..self
+        }
+    }
+
+    /// Node with `phandle`, found through the index given with `with_phandle_index` or by
+    /// walking the tree
+    pub fn node_by_phandle(&self, phandle: u32) -> Option<DeviceTreeNode<'a>> {
+        // 0 and 0xffffffff are never valid phandles
+        if phandle == 0 || phandle == u32::MAX {
+            return None;
+        }
+
+        if let Some(index) = self.phandles {
+            if let Some(offset) = index.get(phandle) {
+                return DeviceTreeNode::parse(self.root_node.blocks(), offset, None).ok();
+            }
+            if index.is_complete() {
+                return None;
+            }
+        }
+
+        self.root_node
+            .descendants()
+            .map(|(_, node)| node)
+            .find(|node| node.phandle() == Some(phandle))
+    }
+
+    pub fn resolve_alias(&self, alias: &str) -> Option<&'a str> {
         for prop in self.aliases_node?.properties() {
             if prop.name() == alias {
                 return prop.value_str();
@@ -128,7 +200,7 @@ impl DtbReader {
         None
     }
 
-    pub fn find_node(&self, path: &str) -> Option<DeviceTreeNode> {
+    pub fn find_node(&self, path: &str) -> Option<DeviceTreeNode<'a>> {
         if path == "/" {
             return Some(self.root_node);
         }
@@ -142,9 +214,103 @@ impl DtbReader {
         let mut current = self.root_node;
 
         for part in real_path[1..].split("/") {
-            current = current.get_child(part)?;
+            // A component without unit address matches the first node with that name
+            current = if part.contains('@') {
+                current.children().find(|c| c.full_name() == part)?
+            } else {
+                current.get_child(part)?
+            };
         }
 
         Some(current)
     }
 }
+
+/// Blob of `totalsize` bytes at `ptr`, only its magic is checked
+///
+/// # Safety
+///
+/// `ptr` must point to a blob of `totalsize` bytes that is never modified.
+pub(crate) unsafe fn blob_at(ptr: *const u32) -> Result<&'static [u8], DtbInitError> {
+    // Only the magic and `totalsize` are read before the blob is bounded
+    let start = unsafe { core::slice::from_raw_parts(ptr as *const u8, 8) };
+    let magic = u32::from_be_bytes(start[0..4].try_into().unwrap());
+    if magic != MAGIC_VALUE {
+        return Err(DtbInitError::InvalidHeader {
+            expected: MAGIC_VALUE,
+            found: magic,
+        });
+    }
+
+    let totalsize = u32::from_be_bytes(start[4..8].try_into().unwrap()) as usize;
+    Ok(unsafe { core::slice::from_raw_parts(ptr as *const u8, totalsize) })
+}
+
+/// Validates the header and the blocks of the blob at the start of `bytes`, then the whole tree.
+/// Returns the blob, its header and its root node.
+pub(crate) fn parse_blob(
+    bytes: &[u8],
+) -> Result<(&[u8], Header, DeviceTreeNode<'_>), DtbInitError> {
+    if bytes.len() < HEADER_SIZE {
+        return Err(DtbInitError::Truncated {
+            expected: HEADER_SIZE,
+            found: bytes.len(),
+        });
+    }
+
+    let header = Header::parse(bytes);
+
+    if header.magic != MAGIC_VALUE {
+        return Err(DtbInitError::InvalidHeader {
+            expected: MAGIC_VALUE,
+            found: header.magic,
+        });
+    }
+
+    if header.version != DTB_VERSION && header.last_comp_version != DTB_VERSION {
+        return Err(DtbInitError::UnsupportedDtbVersion);
+    }
+
+    let totalsize = header.totalsize as usize;
+    if totalsize > bytes.len() {
+        return Err(DtbInitError::Truncated {
+            expected: totalsize,
+            found: bytes.len(),
+        });
+    }
+    let blob = &bytes[..totalsize];
+
+    if !header.off_dt_struct.is_multiple_of(4)
+        || !header.size_dt_struct.is_multiple_of(4)
+        || !header.off_mem_rsvmap.is_multiple_of(8)
+    {
+        return Err(DtbInitError::MisalignedBlock);
+    }
+
+    let in_bounds = |offset: u32, size: u32| {
+        let start = offset as usize;
+        start >= HEADER_SIZE
+            && start
+                .checked_add(size as usize)
+                .is_some_and(|end| end <= totalsize)
+    };
+
+    if !in_bounds(header.off_dt_struct, header.size_dt_struct)
+        || !in_bounds(header.off_dt_strings, header.size_dt_strings)
+        || !in_bounds(header.off_mem_rsvmap, 0)
+    {
+        return Err(DtbInitError::BlockOutOfBounds);
+    }
+
+    let blocks = Blocks::new(
+        blob,
+        header.off_dt_struct as usize,
+        header.size_dt_struct as usize,
+        header.off_dt_strings as usize,
+        header.size_dt_strings as usize,
+    );
+    let root_node = DeviceTreeNode::parse(blocks, 0, None).map_err(DtbInitError::ParsingError)?;
+    root_node.validate().map_err(DtbInitError::ParsingError)?;
+
+    Ok((blob, header, root_node))
+}
@@ -1,38 +1,54 @@
-#[repr(C)]
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct MemoryReserveEntry {
     address: u64,
     size: u64,
 }
 
+impl MemoryReserveEntry {
+    pub fn address(&self) -> u64 {
+        self.address
+    }
+
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+}
+
+const ENTRY_SIZE: usize = 16;
+
+/// Entries of the memory reservation block, stops at the terminating entry or at the end of the blob
 #[derive(Debug, Default)]
-pub struct MemoryReserveEntryIter {
-    start: *const MemoryReserveEntry,
+pub struct MemoryReserveEntryIter<'a> {
+    bytes: &'a [u8],
     curr: usize,
 }
 
-impl MemoryReserveEntryIter {
-    pub fn new(start: *const u32) -> MemoryReserveEntryIter {
-        MemoryReserveEntryIter {
-            start: start as *const MemoryReserveEntry,
-            curr: 0,
-        }
+impl<'a> MemoryReserveEntryIter<'a> {
+    /// `bytes` goes from the start of the memory reservation block to the end of the blob
+    pub fn new(bytes: &'a [u8]) -> MemoryReserveEntryIter<'a> {
+        MemoryReserveEntryIter { bytes, curr: 0 }
     }
 }
 
-impl Iterator for MemoryReserveEntryIter {
+impl Iterator for MemoryReserveEntryIter<'_> {
     type Item = MemoryReserveEntry;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let entry = unsafe { &*self.start.add(self.curr) };
-        self.curr += 1;
-
-        match entry {
-            MemoryReserveEntry {
-                address: 0,
-                size: 0,
-            } => None,
-            _ => Some(*entry),
+        let entry = self.bytes.get(self.curr..self.curr + ENTRY_SIZE)?;
+        self.curr += ENTRY_SIZE;
+
+        let (address, size) = entry.split_at(8);
+        let entry = MemoryReserveEntry {
+            address: u64::from_be_bytes(address.try_into().unwrap()),
+            size: u64::from_be_bytes(size.try_into().unwrap()),
+        };
+
+        if entry.address == 0 && entry.size == 0 {
+            // Terminating entry
+            self.curr = self.bytes.len();
+            return None;
         }
+
+        Some(entry)
     }
 }
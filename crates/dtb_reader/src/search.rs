@@ -0,0 +1,88 @@
+use crate::{Descendants, DeviceTreeNode, DtbReader};
+
+impl<'a> DtbReader<'a> {
+    /// Nodes with `compatible` in their `compatible` list. Nodes where it is the most specific
+    /// entry come first, then the ones where it is second and so on, in tree order for a same
+    /// position.
+    pub fn find_compatible<'c>(&self, compatible: &'c str) -> FindCompatible<'a, 'c> {
+        FindCompatible {
+            root: self.root_node(),
+            compatible,
+            position: 0,
+            nodes: None,
+            longer_list: false,
+        }
+    }
+
+    /// Nodes with a `device_type` property of `device_type`, like `memory` or `cpu`
+    pub fn find_by_device_type<'t>(
+        &self,
+        device_type: &'t str,
+    ) -> impl Iterator<Item = DeviceTreeNode<'a>> + use<'a, 't> {
+        all_nodes(self.root_node()).filter(move |node| {
+            node.get_property("device_type")
+                .and_then(|p| p.value_str())
+                .is_some_and(|t| t == device_type)
+        })
+    }
+}
+
+/// Nodes compatible with a string, see `DtbReader::find_compatible`
+pub struct FindCompatible<'a, 'c> {
+    root: DeviceTreeNode<'a>,
+    compatible: &'c str,
+    // Position in the `compatible` lists matched by the current pass over the tree
+    position: usize,
+    nodes: Option<AllNodes<'a>>,
+    // Whether a list of the current pass has more entries than `position`
+    longer_list: bool,
+}
+
+impl<'a> Iterator for FindCompatible<'a, '_> {
+    type Item = DeviceTreeNode<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // One pass over the tree per position, no allocation needed to sort the nodes
+        loop {
+            let nodes = self.nodes.get_or_insert_with(|| all_nodes(self.root));
+
+            let Some(node) = nodes.next() else {
+                if !self.longer_list {
+                    return None;
+                }
+                self.position += 1;
+                self.nodes = None;
+                self.longer_list = false;
+                continue;
+            };
+
+            let Some(mut list) = node
+                .get_property("compatible")
+                .and_then(|p| p.as_str_list().ok())
+            else {
+                continue;
+            };
+
+            match list.nth(self.position) {
+                Some(c) => {
+                    self.longer_list |= list.next().is_some();
+                    if c == self.compatible {
+                        return Some(node);
+                    }
+                }
+                None => continue,
+            }
+        }
+    }
+}
+
+type AllNodes<'a> = core::iter::Chain<
+    core::iter::Once<DeviceTreeNode<'a>>,
+    core::iter::Map<Descendants<'a>, fn((usize, DeviceTreeNode<'a>)) -> DeviceTreeNode<'a>>,
+>;
+
+/// The root and all its descendants in pre-order
+fn all_nodes(root: DeviceTreeNode) -> AllNodes {
+    let node: fn((usize, DeviceTreeNode)) -> DeviceTreeNode = |(_, node)| node;
+    core::iter::once(root).chain(root.descendants().map(node))
+}
@@ -0,0 +1,153 @@
+use crate::tree::{DeviceTreeNode, NodeProperty};
+
+// Defaults from the Devicetree Specification when a node does not define them
+const DEFAULT_ADDRESS_CELLS: usize = 2;
+const DEFAULT_SIZE_CELLS: usize = 1;
+
+/// Entry of a `ranges` or `dma-ranges` property
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Range {
+    /// Address in the address space of the children of the node
+    pub child_address: u64,
+    /// Address in the address space of the parent of the node
+    pub parent_address: u64,
+    pub size: u64,
+}
+
+impl Range {
+    /// Translates `address` from the child to the parent address space
+    pub fn translate(&self, address: u64) -> Option<u64> {
+        let offset = address.checked_sub(self.child_address)?;
+        if offset >= self.size {
+            return None;
+        }
+
+        self.parent_address.checked_add(offset)
+    }
+}
+
+impl<'a> DeviceTreeNode<'a> {
+    /// `#address-cells` of the node, used by the `reg` of its children
+    pub fn address_cells(&self) -> usize {
+        cells_property(self, "#address-cells").unwrap_or(DEFAULT_ADDRESS_CELLS)
+    }
+
+    /// `#size-cells` of the node, used by the `reg` of its children
+    pub fn size_cells(&self) -> usize {
+        cells_property(self, "#size-cells").unwrap_or(DEFAULT_SIZE_CELLS)
+    }
+
+    /// `(address, size)` entries of the `reg` property, sized by the cells of the parent.
+    /// Addresses are in the address space of the parent, see `translate_address`.
+    ///
+    /// Empty without `reg`, a partial entry at the end is ignored.
+    pub fn reg(&self) -> impl Iterator<Item = (u64, u64)> + use<'a> {
+        let (address_cells, size_cells) = match self.parent() {
+            Some(parent) => (parent.address_cells(), parent.size_cells()),
+            None => (DEFAULT_ADDRESS_CELLS, DEFAULT_SIZE_CELLS),
+        };
+
+        entries(self.get_property("reg"), [address_cells, size_cells])
+            .map(|[address, size]| (address, size))
+    }
+
+    /// Entries of the `ranges` property, `None` when the node does not define it.
+    ///
+    /// An empty `ranges` maps the children address space one to one on the parent one.
+    pub fn ranges(&self) -> Option<impl Iterator<Item = Range> + use<'a>> {
+        self.ranges_property("ranges")
+    }
+
+    /// Entries of the `dma-ranges` property, `None` when the node does not define it.
+    ///
+    /// Same layout as `ranges`, but maps the addresses used by the DMA of the children.
+    pub fn dma_ranges(&self) -> Option<impl Iterator<Item = Range> + use<'a>> {
+        self.ranges_property("dma-ranges")
+    }
+
+    /// Translates an address from the `reg` of the node to a CPU physical address by going through
+    /// the `ranges` of every bus up to the root.
+    ///
+    /// `None` when a bus does not define `ranges` or none of its ranges holds the address.
+    pub fn translate_address(&self, address: u64) -> Option<u64> {
+        let mut address = address;
+        let mut buses = self.ancestors().peekable();
+
+        // The root has no parent
+        buses.peek()?;
+
+        while let Some(bus) = buses.next() {
+            // The address space of the root is the one of the CPU
+            if buses.peek().is_none() {
+                break;
+            }
+
+            let mut ranges = bus.ranges()?.peekable();
+
+            // An empty `ranges` is an identity mapping
+            if ranges.peek().is_some() {
+                address = ranges.find_map(|r| r.translate(address))?;
+            }
+        }
+
+        Some(address)
+    }
+
+    //
+    // NON-PUBLIC INTERFACE
+    //
+
+    fn ranges_property(&self, name: &str) -> Option<impl Iterator<Item = Range> + use<'a>> {
+        let prop = self.get_property(name)?;
+
+        let parent_address_cells = match self.parent() {
+            Some(parent) => parent.address_cells(),
+            None => DEFAULT_ADDRESS_CELLS,
+        };
+        let cells = [
+            self.address_cells(),
+            parent_address_cells,
+            self.size_cells(),
+        ];
+
+        Some(
+            entries(Some(prop), cells).map(|[child_address, parent_address, size]| Range {
+                child_address,
+                parent_address,
+                size,
+            }),
+        )
+    }
+}
+
+fn cells_property(node: &DeviceTreeNode, name: &str) -> Option<usize> {
+    Some(node.get_property(name)?.as_u32().ok()? as usize)
+}
+
+/// Splits the value of `prop` in entries made of `N` numbers of `cells[i]` cells each
+fn entries<'a, const N: usize>(
+    prop: Option<NodeProperty<'a>>,
+    cells: [usize; N],
+) -> impl Iterator<Item = [u64; N]> + use<'a, N> {
+    let value = prop.map(|p| p.raw_value()).unwrap_or_default();
+    let entry_size = cells.iter().sum::<usize>() * 4;
+
+    // `max(1)` keeps `chunks_exact` happy, an entry without cells has no value to yield
+    value
+        .chunks_exact(entry_size.max(1))
+        .filter(move |_| entry_size != 0)
+        .map(move |mut entry| {
+            cells.map(|count| {
+                let (number, rest) = entry.split_at(count * 4);
+                entry = rest;
+                read_cells(number)
+            })
+        })
+}
+
+/// Reads a Big-Endian number made of 32 bits cells, only the low 64 bits are kept
+fn read_cells(bytes: &[u8]) -> u64 {
+    bytes.chunks_exact(4).fold(0, |number, cell| {
+        (number << 32) | u32::from_be_bytes(cell.try_into().unwrap()) as u64
+    })
+}
@@ -0,0 +1,109 @@
+use core::ffi::CStr;
+
+use crate::tree::{ParsingError, ParsingErrorKind, tokens::Tokens};
+
+/// Structure and strings blocks of a blob, every read is bounds checked
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Blocks<'a> {
+    structure: &'a [u8],
+    strings: &'a [u8],
+    // Offsets of the blocks in the blob, errors are reported relative to the blob
+    structure_start: usize,
+    strings_start: usize,
+}
+
+impl<'a> Blocks<'a> {
+    /// Blocks of `blob` at the given offsets, which must be in bounds
+    pub(crate) fn new(
+        blob: &'a [u8],
+        structure_start: usize,
+        structure_size: usize,
+        strings_start: usize,
+        strings_size: usize,
+    ) -> Blocks<'a> {
+        Blocks {
+            structure: &blob[structure_start..structure_start + structure_size],
+            strings: &blob[strings_start..strings_start + strings_size],
+            structure_start,
+            strings_start,
+        }
+    }
+
+    /// Error at `offset` in the structure block
+    pub(crate) fn error(&self, kind: ParsingErrorKind, offset: usize) -> ParsingError {
+        ParsingError {
+            kind,
+            offset: self.structure_start + offset,
+        }
+    }
+
+    /// Big-Endian word at `offset` in the structure block
+    pub(crate) fn u32_at(&self, offset: usize) -> Result<u32, ParsingError> {
+        let bytes = self.bytes_at(offset, 4)?;
+        Ok(u32::from_be_bytes(bytes.try_into().unwrap()))
+    }
+
+    pub(crate) fn token_at(&self, offset: usize) -> Result<Tokens, ParsingError> {
+        Tokens::try_from(self.u32_at(offset)?).map_err(|kind| self.error(kind, offset))
+    }
+
+    /// `length` bytes at `offset` in the structure block
+    pub(crate) fn bytes_at(&self, offset: usize, length: usize) -> Result<&'a [u8], ParsingError> {
+        offset
+            .checked_add(length)
+            .and_then(|end| self.structure.get(offset..end))
+            .ok_or(self.error(ParsingErrorKind::OutOfBounds, offset))
+    }
+
+    /// Offset of the first token that is not a NOP, starting at `offset`
+    pub(crate) fn skip_nops(&self, offset: usize) -> Result<usize, ParsingError> {
+        let mut curr = offset;
+        while matches!(self.token_at(curr)?, Tokens::Nop) {
+            curr += 4;
+        }
+        Ok(curr)
+    }
+
+    /// Offset of the token following the property at `offset`
+    pub(crate) fn skip_property(&self, offset: usize) -> Result<usize, ParsingError> {
+        let length = self.u32_at(offset + 4)? as usize;
+        align_token(length)
+            .checked_add(offset + 12)
+            .ok_or(self.error(ParsingErrorKind::OutOfBounds, offset))
+    }
+
+    /// Node name at `offset` in the structure block and the offset of the token following it
+    pub(crate) fn node_name_at(&self, offset: usize) -> Result<(&'a str, usize), ParsingError> {
+        let name = c_str_at(self.structure, offset).map_err(|(kind, at)| self.error(kind, at))?;
+
+        Ok((name, offset + align_token(name.len() + 1)))
+    }
+
+    /// String at `offset` in the strings block
+    pub(crate) fn string_at(&self, offset: usize) -> Result<&'a str, ParsingError> {
+        c_str_at(self.strings, offset).map_err(|(kind, at)| ParsingError {
+            kind,
+            offset: self.strings_start + at,
+        })
+    }
+}
+
+/// NUL terminated UTF-8 string at `offset` in `block`, errors come with their offset in `block`
+fn c_str_at(block: &[u8], offset: usize) -> Result<&str, (ParsingErrorKind, usize)> {
+    let string = block
+        .get(offset..)
+        .and_then(|bytes| CStr::from_bytes_until_nul(bytes).ok())
+        .ok_or((ParsingErrorKind::OutOfBounds, offset))?;
+
+    string.to_str().map_err(|e| {
+        (
+            ParsingErrorKind::InvalidUtf8NodeName(e),
+            offset + e.valid_up_to(),
+        )
+    })
+}
+
+/// Rounds `size` up to the 4 bytes alignment of tokens
+pub(crate) fn align_token(size: usize) -> usize {
+    size.div_ceil(4) * 4
+}
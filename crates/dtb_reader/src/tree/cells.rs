@@ -0,0 +1,51 @@
+use core::{iter::Map, slice::ChunksExact};
+
+/// Big-Endian 32 bits cells borrowed from the blob
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Cells<'a> {
+    // Length is a multiple of 4
+    bytes: &'a [u8],
+}
+
+impl<'a> Cells<'a> {
+    /// Cells of `bytes`, `None` when the length is not a multiple of 4
+    pub fn new(bytes: &'a [u8]) -> Option<Cells<'a>> {
+        bytes.len().is_multiple_of(4).then_some(Cells { bytes })
+    }
+
+    /// Number of cells
+    pub fn len(&self) -> usize {
+        self.bytes.len() / 4
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    pub fn get(&self, index: usize) -> Option<u32> {
+        self.bytes.get(index * 4..index * 4 + 4).map(read_cell)
+    }
+
+    pub fn iter(&self) -> CellsIter<'a> {
+        self.bytes.chunks_exact(4).map(read_cell)
+    }
+
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+}
+
+pub type CellsIter<'a> = Map<ChunksExact<'a, u8>, fn(&[u8]) -> u32>;
+
+impl<'a> IntoIterator for Cells<'a> {
+    type Item = u32;
+    type IntoIter = CellsIter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+fn read_cell(cell: &[u8]) -> u32 {
+    u32::from_be_bytes(cell.try_into().unwrap())
+}
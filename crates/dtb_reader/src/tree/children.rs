@@ -1,39 +1,52 @@
 use crate::{
     DeviceTreeNode,
-    tree::tokens::{Tokens, skip_nops},
+    tree::{Blocks, ParsingError, tokens::Tokens},
 };
 
 #[derive(Debug, Clone, Copy)]
-pub struct ChildNodeIter {
-    curr: Option<*const u32>,
-    str_block_ptr: *const u8,
+pub struct ChildNodeIter<'a> {
+    curr: Option<usize>,
+    blocks: Blocks<'a>,
+    // Offset of the node whose children are iterated
+    parent: usize,
 }
 
-impl ChildNodeIter {
-    pub fn new(start: Option<*const u32>, str_block_ptr: *const u8) -> ChildNodeIter {
+impl<'a> ChildNodeIter<'a> {
+    pub(crate) fn new(
+        start: Option<usize>,
+        blocks: Blocks<'a>,
+        parent: usize,
+    ) -> ChildNodeIter<'a> {
         ChildNodeIter {
             curr: start,
-            str_block_ptr,
+            blocks,
+            parent,
         }
     }
 }
 
-impl Iterator for ChildNodeIter {
-    type Item = DeviceTreeNode;
+impl<'a> ChildNodeIter<'a> {
+    fn parse_next(&mut self, curr: usize) -> Result<Option<DeviceTreeNode<'a>>, ParsingError> {
+        let curr = self.blocks.skip_nops(curr)?;
 
-    fn next(&mut self) -> Option<Self::Item> {
-        let mut curr_ptr = self.curr.take()?;
-
-        curr_ptr = skip_nops(curr_ptr).unwrap();
-
-        let token = unsafe { Tokens::try_from(*curr_ptr).unwrap() };
+        let token = self.blocks.token_at(curr)?;
         if !matches!(token, Tokens::BeginNode) {
-            return None;
+            return Ok(None);
         }
 
-        let node = DeviceTreeNode::parse(curr_ptr, self.str_block_ptr).unwrap();
-        self.curr = Some(unsafe { node.end().add(1) }); // Add 1 to skip EndNode
+        let node = DeviceTreeNode::parse(self.blocks, curr, Some(self.parent))?;
+        self.curr = Some(node.end() + 4); // Add 4 to skip EndNode
+
+        Ok(Some(node))
+    }
+}
 
-        Some(node)
+impl<'a> Iterator for ChildNodeIter<'a> {
+    type Item = Result<DeviceTreeNode<'a>, ParsingError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // `curr` stays empty after the last child or an error
+        let curr = self.curr.take()?;
+        self.parse_next(curr).transpose()
     }
 }
@@ -0,0 +1,210 @@
+use core::fmt::{self, Display, Write};
+
+use crate::tree::{Blocks, DeviceTreeNode, MAX_DEPTH, NodeProperty, tokens::Tokens};
+
+impl<'a> DeviceTreeNode<'a> {
+    /// Parent, grand-parent and so on up to the root
+    ///
+    /// They are found in one pass over the tokens from the root.
+    pub fn ancestors(&self) -> Ancestors<'a> {
+        Ancestors {
+            blocks: self.blocks(),
+            offsets: self.ancestor_offsets(),
+        }
+    }
+
+    /// Number of ancestors, 0 for the root
+    pub fn depth(&self) -> usize {
+        self.ancestor_offsets().len()
+    }
+
+    /// Full path of the node, like `/soc/serial@10000000`
+    pub fn path(&self) -> NodePath<'a> {
+        NodePath(*self)
+    }
+
+    /// Writes the full path of the node without allocating
+    pub fn write_path(&self, w: &mut impl Write) -> fmt::Result {
+        let ancestors = self.ancestor_offsets();
+        if ancestors.len() == 0 {
+            return w.write_char('/');
+        }
+
+        // The root has an empty name, its separator is the one of its child
+        for &offset in &ancestors.offsets()[1..] {
+            let (name, _) = self
+                .blocks()
+                .node_name_at(offset as usize + 4)
+                .map_err(|_| fmt::Error)?;
+            write!(w, "/{name}")?;
+        }
+        write!(w, "/{}", self.full_name())
+    }
+
+    /// Every node under this one in pre-order, with its depth relative to this node
+    /// (1 for the children)
+    pub fn descendants(&self) -> Descendants<'a> {
+        let mut open = AncestorOffsets::EMPTY;
+        let started = open.push(self.offset()).is_some();
+
+        Descendants {
+            blocks: self.blocks(),
+            curr: self.children_offset().filter(|_| started),
+            end: self.end(),
+            open,
+        }
+    }
+
+    /// Property of the node or of its closest ancestor defining it, like `interrupt-parent`
+    pub fn inherited_property(&self, name: &str) -> Option<NodeProperty<'a>> {
+        self.get_property(name)
+            .or_else(|| self.ancestors().find_map(|a| a.get_property(name)))
+    }
+
+    //
+    // NON-PUBLIC INTERFACE
+    //
+
+    /// Offsets of the ancestors of the node, in one pass over the tokens from the root.
+    ///
+    /// Empty for a malformed tree, the trees of a `DtbReader` are validated.
+    pub(crate) fn ancestor_offsets(&self) -> AncestorOffsets {
+        let blocks = self.blocks();
+        let mut ancestors = AncestorOffsets::EMPTY;
+        let mut curr = 0;
+
+        // Nodes come in pre-order, the nodes still open when the walk reaches this one are its
+        // ancestors
+        while curr < self.offset() {
+            let next = match blocks.token_at(curr) {
+                Ok(Tokens::BeginNode) => ancestors
+                    .push(curr)
+                    .and_then(|_| blocks.node_name_at(curr + 4).ok())
+                    .map(|(_, next)| next),
+                Ok(Tokens::EndNode) => ancestors.pop().map(|_| curr + 4),
+                Ok(Tokens::Property) => blocks.skip_property(curr).ok(),
+                Ok(Tokens::Nop) => Some(curr + 4),
+                Ok(Tokens::End) | Err(_) => None,
+            };
+
+            match next {
+                Some(next) => curr = next,
+                None => return AncestorOffsets::EMPTY,
+            }
+        }
+
+        ancestors
+    }
+}
+
+/// Offsets in the structure block of nested nodes, the outermost first
+///
+/// Trees are at most `MAX_DEPTH` deep, the extra slot holds the deepest nodes while walking under
+/// them.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct AncestorOffsets {
+    offsets: [u32; MAX_DEPTH + 1],
+    len: usize,
+}
+
+impl AncestorOffsets {
+    pub(crate) const EMPTY: AncestorOffsets = AncestorOffsets {
+        offsets: [0; MAX_DEPTH + 1],
+        len: 0,
+    };
+
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+
+    pub(crate) fn offsets(&self) -> &[u32] {
+        &self.offsets[..self.len]
+    }
+
+    pub(crate) fn last(&self) -> Option<usize> {
+        self.offsets().last().map(|&offset| offset as usize)
+    }
+
+    /// `None` when the offset does not fit
+    pub(crate) fn push(&mut self, offset: usize) -> Option<()> {
+        *self.offsets.get_mut(self.len)? = offset.try_into().ok()?;
+        self.len += 1;
+        Some(())
+    }
+
+    pub(crate) fn pop(&mut self) -> Option<usize> {
+        let offset = self.last()?;
+        self.len -= 1;
+        Some(offset)
+    }
+}
+
+/// Full path of a node, see `DeviceTreeNode::path`
+#[derive(Debug, Clone, Copy)]
+pub struct NodePath<'a>(DeviceTreeNode<'a>);
+
+impl Display for NodePath<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.write_path(f)
+    }
+}
+
+/// Ancestors of a node, the closest first, see `DeviceTreeNode::ancestors`
+#[derive(Debug, Clone, Copy)]
+pub struct Ancestors<'a> {
+    blocks: Blocks<'a>,
+    // Ancestors not yielded yet
+    offsets: AncestorOffsets,
+}
+
+impl<'a> Iterator for Ancestors<'a> {
+    type Item = DeviceTreeNode<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let offset = self.offsets.pop()?;
+        DeviceTreeNode::parse(self.blocks, offset, self.offsets.last()).ok()
+    }
+}
+
+/// Pre-order walk of a subtree, see `DeviceTreeNode::descendants`
+#[derive(Debug, Clone, Copy)]
+pub struct Descendants<'a> {
+    blocks: Blocks<'a>,
+    curr: Option<usize>,
+    // EndNode of the node the walk started from
+    end: usize,
+    // Nodes the walk is in, from the one it started from
+    open: AncestorOffsets,
+}
+
+impl<'a> Iterator for Descendants<'a> {
+    type Item = (usize, DeviceTreeNode<'a>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // Nodes come in pre-order in the structure block, so the walk follows the tokens
+        loop {
+            let curr = self.blocks.skip_nops(self.curr.take()?).ok()?;
+            if curr >= self.end {
+                return None;
+            }
+
+            match self.blocks.token_at(curr).ok()? {
+                Tokens::BeginNode => {
+                    let node = DeviceTreeNode::parse(self.blocks, curr, self.open.last()).ok()?;
+                    self.open.push(curr)?;
+                    self.curr = Some(self.blocks.node_name_at(curr + 4).ok()?.1);
+
+                    return Some((self.open.len() - 1, node));
+                }
+                Tokens::EndNode => {
+                    self.open.pop()?;
+                    self.curr = Some(curr + 4);
+                }
+                Tokens::Property => {
+                    self.curr = Some(self.blocks.skip_property(curr).ok()?);
+                }
+                Tokens::Nop | Tokens::End => return None,
+            }
+        }
+    }
+}
@@ -1,6 +1,19 @@
+mod address;
+mod blocks;
+mod cells;
 mod children;
+mod hierarchy;
 mod node;
+mod phandle;
 mod properties;
 mod tokens;
 
-pub use node::{DeviceTreeNode, ParsingError};
+pub use address::Range;
+pub(crate) use blocks::Blocks;
+pub use cells::{Cells, CellsIter};
+pub use hierarchy::{Ancestors, Descendants, NodePath};
+pub use node::{DeviceTreeNode, MAX_DEPTH, ParsingError, ParsingErrorKind};
+pub use phandle::PhandleList;
+pub use properties::{NodeProperty, PropertyError};
+#[cfg(feature = "alloc")]
+pub(crate) use tokens::Tokens;
@@ -1,62 +1,141 @@
-use core::{ffi::CStr, str::Utf8Error};
+use core::str::Utf8Error;
 
 use crate::tree::{
+    Blocks,
     children::ChildNodeIter,
-    properties::{NodeProperty, PropertyIter},
-    tokens::{Tokens, skip_nops},
+    properties::{NodeProperty, PropertyError, PropertyIter},
+    tokens::Tokens,
 };
 
 #[derive(Debug)]
-pub enum ParsingError {
+pub enum ParsingErrorKind {
     InvalidToken,
     MalformedTree,
-    UnexpectedToken { expected: Tokens, found: Tokens },
+    UnexpectedToken {
+        expected: Tokens,
+        found: Tokens,
+    },
     EarlyEnd,
     InvalidUtf8NodeName(Utf8Error),
+    /// Read past the end of a block, or a string without NUL terminator
+    OutOfBounds,
+    /// Node nested more than `MAX_DEPTH` levels under the root
+    TooDeep,
 }
 
-impl From<Utf8Error> for ParsingError {
-    fn from(value: Utf8Error) -> Self {
-        Self::InvalidUtf8NodeName(value)
-    }
+/// Deepest node accepted under the root, like `FDT_MAX_DEPTH` of libfdt. Walking a tree never
+/// needs more than a bounded stack.
+pub const MAX_DEPTH: usize = 32;
+
+#[derive(Debug)]
+pub struct ParsingError {
+    pub kind: ParsingErrorKind,
+    /// Offset from the start of the blob
+    pub offset: usize,
 }
 
 #[derive(Debug, Clone, Copy)]
-pub struct DeviceTreeNode {
-    name: &'static str,
-    props_ptr: Option<*const u32>,
-    children_ptr: Option<*const u32>,
-    str_block_ptr: *const u8,
-    end_ptr: *const u32,
+pub struct DeviceTreeNode<'a> {
+    name: &'a str,
+    blocks: Blocks<'a>,
+    // Offsets in the structure block
+    offset: usize,
+    // Parent of a node reached from it, the other nodes find their parent from the root
+    parent: Option<usize>,
+    props_offset: Option<usize>,
+    children_offset: Option<usize>,
+    end_offset: usize,
 }
 
-impl DeviceTreeNode {
-    pub fn full_name(&self) -> &'static str {
+impl<'a> DeviceTreeNode<'a> {
+    pub fn full_name(&self) -> &'a str {
         self.name
     }
 
-    pub fn name(&self) -> &'static str {
+    pub fn name(&self) -> &'a str {
         self.name.split("@").nth(0).unwrap()
     }
 
-    pub fn address(&self) -> Option<&'static str> {
+    pub fn address(&self) -> Option<&'a str> {
         self.name.split("@").nth(1)
     }
 
-    pub fn properties(&self) -> impl Iterator<Item = NodeProperty> {
-        PropertyIter::new(self.props_ptr, self.str_block_ptr)
+    /// Properties of the node, stops at the first malformed one
+    ///
+    /// Never stops early for nodes of a `DtbReader`, the whole tree is validated when it is created.
+    pub fn properties(&self) -> impl Iterator<Item = NodeProperty<'a>> + use<'a> {
+        self.try_properties().map_while(Result::ok)
     }
 
-    pub fn children(&self) -> impl Iterator<Item = DeviceTreeNode> {
-        ChildNodeIter::new(self.children_ptr, self.str_block_ptr)
+    /// Children of the node, stops at the first malformed one
+    ///
+    /// Never stops early for nodes of a `DtbReader`, the whole tree is validated when it is created.
+    pub fn children(&self) -> impl Iterator<Item = DeviceTreeNode<'a>> + use<'a> {
+        self.try_children().map_while(Result::ok)
     }
 
-    pub fn get_child(&self, name: &str) -> Option<DeviceTreeNode> {
-        ChildNodeIter::new(self.children_ptr, self.str_block_ptr).find(|c| c.name() == name)
+    /// Properties of the node, ends after the first error
+    pub fn try_properties(
+        &self,
+    ) -> impl Iterator<Item = Result<NodeProperty<'a>, ParsingError>> + use<'a> {
+        PropertyIter::new(self.props_offset, self.blocks)
     }
 
-    pub fn get_property(&self, name: &str) -> Option<NodeProperty> {
-        PropertyIter::new(self.props_ptr, self.str_block_ptr).find(|p| p.name() == name)
+    /// Children of the node, ends after the first error
+    pub fn try_children(
+        &self,
+    ) -> impl Iterator<Item = Result<DeviceTreeNode<'a>, ParsingError>> + use<'a> {
+        ChildNodeIter::new(self.children_offset, self.blocks, self.offset)
+    }
+
+    /// Parent of the node, `None` for the root
+    ///
+    /// Nodes reached through `children` or `descendants` link to their parent, so it is a single
+    /// parse. The other nodes find it in one pass over the tokens from the root.
+    pub fn parent(&self) -> Option<DeviceTreeNode<'a>> {
+        match self.parent {
+            Some(offset) => DeviceTreeNode::parse(self.blocks, offset, None).ok(),
+            None => self.ancestors().next(),
+        }
+    }
+
+    pub fn get_child(&self, name: &str) -> Option<DeviceTreeNode<'a>> {
+        self.children().find(|c| c.name() == name)
+    }
+
+    pub fn get_property(&self, name: &str) -> Option<NodeProperty<'a>> {
+        self.properties().find(|p| p.name() == name)
+    }
+
+    /// Whether the device is usable, a node without `status` is enabled
+    pub fn is_enabled(&self) -> bool {
+        match self.get_property("status") {
+            None => true,
+            Some(status) => matches!(status.value_str(), Some("okay" | "ok")),
+        }
+    }
+
+    /// Position of `compatible` in the `compatible` list of the node, 0 is the most specific
+    pub fn compatible_position(&self, compatible: &str) -> Option<usize> {
+        self.get_property("compatible")?
+            .as_str_list()
+            .ok()?
+            .position(|c| c == compatible)
+    }
+
+    /// Whether the node has `compatible` in its `compatible` list
+    pub fn is_compatible(&self, compatible: &str) -> bool {
+        self.compatible_position(compatible).is_some()
+    }
+
+    /// Whether the boolean property `name` is set, like `interrupt-controller`.
+    /// Boolean properties have no value.
+    pub fn is_present(&self, name: &str) -> Result<bool, PropertyError> {
+        match self.get_property(name) {
+            None => Ok(false),
+            Some(prop) if prop.raw_value().is_empty() => Ok(true),
+            Some(prop) => Err(prop.length_error(0)),
+        }
     }
 
     //
@@ -65,89 +144,132 @@ impl DeviceTreeNode {
 
     // https://devicetree-specification.readthedocs.io/en/stable/flattened-format.html#tree-structure
     pub(crate) fn parse(
-        node_start_ptr: *const u32,
-        str_block_ptr: *const u8,
-    ) -> Result<DeviceTreeNode, ParsingError> {
-        let mut curr = skip_nops(node_start_ptr)?;
-
-        unsafe {
-            let node = Tokens::try_from(*curr)?;
-            if !matches!(node, Tokens::BeginNode) {
-                return Err(ParsingError::UnexpectedToken {
+        blocks: Blocks<'a>,
+        node_offset: usize,
+        parent: Option<usize>,
+    ) -> Result<DeviceTreeNode<'a>, ParsingError> {
+        let node_offset = blocks.skip_nops(node_offset)?;
+        let mut curr = node_offset;
+
+        let node = blocks.token_at(curr)?;
+        if !matches!(node, Tokens::BeginNode) {
+            return Err(blocks.error(
+                ParsingErrorKind::UnexpectedToken {
                     expected: Tokens::BeginNode,
                     found: node,
-                });
-            }
-            curr = curr.add(1);
-
-            let name_cstr = CStr::from_ptr(curr as *const u8);
-            let name = name_cstr.to_str()?;
-            curr = curr.add((name_cstr.count_bytes() + 1).div_ceil(4));
+                },
+                curr,
+            ));
+        }
 
-            curr = skip_nops(curr)?;
+        let (name, after_name) = blocks.node_name_at(curr + 4)?;
+        curr = blocks.skip_nops(after_name)?;
 
-            let props_ptr = curr;
+        let props_offset = curr;
 
-            let mut children_ptr = None;
-            let mut depth = 0;
-            while let Ok(token) = Tokens::try_from(*curr) {
-                match token {
-                    Tokens::BeginNode => {
-                        depth += 1;
+        let mut children_offset = None;
+        let mut depth = 0;
+        loop {
+            match blocks.token_at(curr)? {
+                Tokens::BeginNode => {
+                    depth += 1;
 
-                        if children_ptr.is_none() {
-                            children_ptr = Some(curr);
-                        }
+                    if children_offset.is_none() {
+                        children_offset = Some(curr);
+                    }
 
-                        curr = curr.add(1);
+                    // Skip name
+                    curr = blocks.node_name_at(curr + 4)?.1;
+                }
+                Tokens::EndNode => {
+                    if depth == 0 {
+                        // End of current node
+                        let has_props = props_offset != curr;
 
-                        // Skip name
-                        let name_cstr = CStr::from_ptr(curr as *const u8);
-                        curr = curr.add((name_cstr.count_bytes() + 1).div_ceil(4));
-                    }
-                    Tokens::EndNode => {
-                        if depth == 0 {
-                            // End of current node
-                            let end_ptr = curr;
-                            let has_props = props_ptr != curr;
-
-                            let node = DeviceTreeNode {
-                                name,
-                                props_ptr: if has_props { Some(props_ptr) } else { None },
-                                children_ptr,
-                                str_block_ptr,
-                                end_ptr,
-                            };
-                            return Ok(node);
-                        } else {
-                            // End of nested node
-                            depth -= 1;
-                            curr = curr.add(1);
-                        }
-                    }
-                    Tokens::End => {
-                        // Should not happen, EndNode should be before it
-                        return Err(ParsingError::EarlyEnd);
-                    }
-                    Tokens::Property => {
-                        // Skip property
-                        let length = u32::from_be(*curr.add(1));
-                        curr = curr.add(3 + length.div_ceil(4) as usize);
-                    }
-                    Tokens::Nop => {
-                        // Skip NOP
-                        curr = curr.add(1);
+                        return Ok(DeviceTreeNode {
+                            name,
+                            blocks,
+                            offset: node_offset,
+                            parent,
+                            props_offset: if has_props { Some(props_offset) } else { None },
+                            children_offset,
+                            end_offset: curr,
+                        });
+                    } else {
+                        // End of nested node
+                        depth -= 1;
+                        curr += 4;
                     }
                 }
-                curr = skip_nops(curr)?;
+                Tokens::End => {
+                    // Should not happen, EndNode should be before it
+                    return Err(blocks.error(ParsingErrorKind::EarlyEnd, curr));
+                }
+                Tokens::Property => {
+                    curr = blocks.skip_property(curr)?;
+                }
+                Tokens::Nop => {
+                    // Skip NOP
+                    curr += 4;
+                }
             }
-
-            // Return the error that caused the escape from the loop
-            Err(Tokens::try_from(*curr).err().unwrap())
+            curr = blocks.skip_nops(curr)?;
         }
     }
 
-    pub(crate) fn end(&self) -> *const u32 {
-        self.end_ptr
+    pub(crate) fn end(&self) -> usize {
+        self.end_offset
+    }
+
+    pub(crate) fn offset(&self) -> usize {
+        self.offset
+    }
+
+    pub(crate) fn blocks(&self) -> Blocks<'a> {
+        self.blocks
+    }
+
+    pub(crate) fn children_offset(&self) -> Option<usize> {
+        self.children_offset
+    }
+
+    /// Checks every property and descendant, so the infallible iterators never stop early.
+    ///
+    /// The tokens are read in a single pass, the depth of the tree cannot exhaust the stack.
+    pub(crate) fn validate(&self) -> Result<(), ParsingError> {
+        let mut curr = self
+            .props_offset
+            .or(self.children_offset)
+            .unwrap_or(self.end_offset);
+        // Depth of the next node, the children of the node are at 1
+        let mut depth = 1;
+
+        loop {
+            curr = self.blocks.skip_nops(curr)?;
+            match self.blocks.token_at(curr)? {
+                Tokens::BeginNode => {
+                    if depth > MAX_DEPTH {
+                        return Err(self.blocks.error(ParsingErrorKind::TooDeep, curr));
+                    }
+                    depth += 1;
+                    curr = self.blocks.node_name_at(curr + 4)?.1;
+                }
+                Tokens::EndNode => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Ok(());
+                    }
+                    curr += 4;
+                }
+                Tokens::Property => {
+                    PropertyIter::new(Some(curr), self.blocks)
+                        .next()
+                        .transpose()?;
+                    curr = self.blocks.skip_property(curr)?;
+                }
+                Tokens::Nop => curr += 4,
+                Tokens::End => return Err(self.blocks.error(ParsingErrorKind::EarlyEnd, curr)),
+            }
+        }
     }
 }
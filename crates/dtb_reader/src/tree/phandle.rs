@@ -0,0 +1,134 @@
+use crate::{
+    DtbReader,
+    tree::{Cells, DeviceTreeNode},
+};
+
+impl<'a> DeviceTreeNode<'a> {
+    /// Identifier other nodes use to reference this one, from `phandle` or `linux,phandle`
+    pub fn phandle(&self) -> Option<u32> {
+        let prop = self
+            .get_property("phandle")
+            .or_else(|| self.get_property("linux,phandle"))?;
+
+        prop.as_u32().ok()
+    }
+
+    /// Node referenced by the single phandle property `name`, like `regmap`
+    pub fn reference(&self, dtb: &DtbReader<'a>, name: &str) -> Option<DeviceTreeNode<'a>> {
+        dtb.node_by_phandle(self.get_property(name)?.as_u32().ok()?)
+    }
+
+    /// Interrupt controller of the node, from the `interrupt-parent` of the node or of its closest
+    /// ancestor
+    pub fn interrupt_parent(&self, dtb: &DtbReader<'a>) -> Option<DeviceTreeNode<'a>> {
+        let phandle = self.inherited_property("interrupt-parent")?.as_u32().ok()?;
+        dtb.node_by_phandle(phandle)
+    }
+
+    /// Entries of a phandle list like `clocks`, each is the referenced node and its arguments.
+    /// The number of arguments is read from the `cells_name` property of the referenced node,
+    /// like `#clock-cells`, and is 0 when it does not define it.
+    ///
+    /// Empty entries (phandle 0) are skipped. Stops at the first entry that references an
+    /// unknown node or is truncated.
+    pub fn phandle_list<'r>(
+        &self,
+        dtb: &'r DtbReader<'a>,
+        name: &str,
+        cells_name: &'r str,
+    ) -> PhandleList<'a, 'r> {
+        PhandleList {
+            dtb,
+            value: self
+                .get_property(name)
+                .map(|p| p.raw_value())
+                .unwrap_or(&[]),
+            cells_name,
+        }
+    }
+
+    /// Entry of the phandle list `name` at the position of `entry` in the `names` property,
+    /// like `clock-names`
+    pub fn phandle_by_name(
+        &self,
+        dtb: &DtbReader<'a>,
+        name: &str,
+        cells_name: &str,
+        names: &str,
+        entry: &str,
+    ) -> Option<(DeviceTreeNode<'a>, Cells<'a>)> {
+        let position = self
+            .get_property(names)?
+            .as_str_list()
+            .ok()?
+            .position(|n| n == entry)?;
+
+        let mut list = self.phandle_list(dtb, name, cells_name);
+        for _ in 0..position {
+            list.next_entry()?;
+        }
+
+        list.next_entry()?
+    }
+
+    /// Clock named `name` in `clock-names`, with its specifier
+    pub fn clock_by_name(
+        &self,
+        dtb: &DtbReader<'a>,
+        name: &str,
+    ) -> Option<(DeviceTreeNode<'a>, Cells<'a>)> {
+        self.phandle_by_name(dtb, "clocks", "#clock-cells", "clock-names", name)
+    }
+}
+
+/// Entries of a phandle list, see `DeviceTreeNode::phandle_list`
+pub struct PhandleList<'a, 'r> {
+    dtb: &'r DtbReader<'a>,
+    // Entries left
+    value: &'a [u8],
+    cells_name: &'r str,
+}
+
+impl<'a> PhandleList<'a, '_> {
+    /// Next entry, `Some(None)` for an empty entry
+    fn next_entry(&mut self) -> Option<Option<(DeviceTreeNode<'a>, Cells<'a>)>> {
+        let (phandle, rest) = self.value.split_first_chunk::<4>()?;
+        let phandle = u32::from_be_bytes(*phandle);
+
+        if phandle == 0 {
+            self.value = rest;
+            return Some(None);
+        }
+
+        // The size of the entry is unknown without its target, nothing after it can be read
+        let Some(node) = self.dtb.node_by_phandle(phandle) else {
+            self.value = &[];
+            return None;
+        };
+
+        let count = node
+            .get_property(self.cells_name)
+            .and_then(|p| p.as_u32().ok())
+            .unwrap_or(0) as usize;
+
+        let Some(args) = count.checked_mul(4).and_then(|size| rest.get(..size)) else {
+            self.value = &[];
+            return None;
+        };
+        self.value = &rest[args.len()..];
+
+        Some(Some((node, Cells::new(args)?)))
+    }
+}
+
+impl<'a> Iterator for PhandleList<'a, '_> {
+    type Item = (DeviceTreeNode<'a>, Cells<'a>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(entry) = self.next_entry()? {
+                return Some(entry);
+            }
+        }
+    }
+}
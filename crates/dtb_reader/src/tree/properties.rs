@@ -1,74 +1,159 @@
-use core::{ffi::CStr, ptr::slice_from_raw_parts};
+use core::{ffi::CStr, str::Utf8Error};
+
+use crate::tree::{Blocks, Cells, ParsingError, blocks::align_token, tokens::Tokens};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PropertyError {
+    /// The value does not have the size of the requested type
+    InvalidLength {
+        expected: usize,
+        found: usize,
+    },
+    /// The value is not made of whole entries of `entry_size` bytes
+    PartialEntry {
+        entry_size: usize,
+        found: usize,
+    },
+    /// A string does not end with a NUL, or there are bytes after it
+    InvalidString,
+    InvalidUtf8(Utf8Error),
+}
 
-use crate::tree::tokens::{Tokens, skip_nops};
+impl From<Utf8Error> for PropertyError {
+    fn from(value: Utf8Error) -> Self {
+        Self::InvalidUtf8(value)
+    }
+}
 
 #[derive(Debug, Clone, Copy)]
-pub struct NodeProperty {
-    name: &'static str,
-    value: &'static [u8],
+pub struct NodeProperty<'a> {
+    name: &'a str,
+    value: &'a [u8],
 }
 
-impl NodeProperty {
-    pub fn name(&self) -> &str {
+impl<'a> NodeProperty<'a> {
+    pub fn name(&self) -> &'a str {
         self.name
     }
 
-    pub fn raw_value(&self) -> &[u8] {
+    pub fn raw_value(&self) -> &'a [u8] {
         self.value
     }
 
-    pub fn value_str(&self) -> Option<&'static str> {
-        let cstr = CStr::from_bytes_until_nul(self.value).ok()?;
-        cstr.to_str().ok()
+    /// Value as a single string, `None` if it is not exactly one NUL terminated string
+    pub fn value_str(&self) -> Option<&'a str> {
+        self.as_str().ok()
+    }
+
+    /// Value as a single NUL terminated string
+    pub fn as_str(&self) -> Result<&'a str, PropertyError> {
+        let cstr =
+            CStr::from_bytes_with_nul(self.value).map_err(|_| PropertyError::InvalidString)?;
+        Ok(cstr.to_str()?)
+    }
+
+    /// Value as a list of NUL terminated strings, like `compatible`
+    pub fn as_str_list(&self) -> Result<impl Iterator<Item = &'a str> + use<'a>, PropertyError> {
+        let Some((&0, strings)) = self.value.split_last() else {
+            return Err(PropertyError::InvalidString);
+        };
+        core::str::from_utf8(strings)?;
+
+        // Validated above, the split happens on ASCII NULs only
+        Ok(strings
+            .split(|&b| b == 0)
+            .map(|s| core::str::from_utf8(s).unwrap_or_default()))
+    }
+
+    /// Value as a single cell
+    pub fn as_u32(&self) -> Result<u32, PropertyError> {
+        let bytes = self.value.try_into().map_err(|_| self.length_error(4))?;
+        Ok(u32::from_be_bytes(bytes))
+    }
+
+    /// Value as two cells, the first one holds the high bits
+    pub fn as_u64(&self) -> Result<u64, PropertyError> {
+        let bytes = self.value.try_into().map_err(|_| self.length_error(8))?;
+        Ok(u64::from_be_bytes(bytes))
+    }
+
+    /// Value as a list of cells
+    pub fn as_cells(&self) -> Result<Cells<'a>, PropertyError> {
+        Cells::new(self.value).ok_or(PropertyError::PartialEntry {
+            entry_size: 4,
+            found: self.value.len(),
+        })
+    }
+
+    /// Value as entries of `n` cells, like the `(phandle, argument)` pairs of `interrupts-extended`
+    pub fn as_cells_chunked(
+        &self,
+        n: usize,
+    ) -> Result<impl Iterator<Item = Cells<'a>> + use<'a>, PropertyError> {
+        let entry_size = n * 4;
+        if n == 0 || !self.value.len().is_multiple_of(entry_size) {
+            return Err(PropertyError::PartialEntry {
+                entry_size,
+                found: self.value.len(),
+            });
+        }
+
+        Ok(self.value.chunks_exact(entry_size).filter_map(Cells::new))
+    }
+
+    //
+    // NON-PUBLIC INTERFACE
+    //
+
+    pub(crate) fn length_error(&self, expected: usize) -> PropertyError {
+        PropertyError::InvalidLength {
+            expected,
+            found: self.value.len(),
+        }
     }
 }
 
 #[derive(Debug, Clone, Copy)]
-pub struct PropertyIter {
-    curr: Option<*const u32>,
-    str_block_ptr: *const u8,
+pub struct PropertyIter<'a> {
+    curr: Option<usize>,
+    blocks: Blocks<'a>,
 }
 
-impl PropertyIter {
-    pub fn new(start: Option<*const u32>, str_block_ptr: *const u8) -> PropertyIter {
+impl<'a> PropertyIter<'a> {
+    pub(crate) fn new(start: Option<usize>, blocks: Blocks<'a>) -> PropertyIter<'a> {
         PropertyIter {
             curr: start,
-            str_block_ptr,
+            blocks,
         }
     }
 }
 
-impl Iterator for PropertyIter {
-    type Item = NodeProperty;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        let mut curr_ptr = self.curr.take()?;
+impl<'a> PropertyIter<'a> {
+    fn parse_next(&mut self, curr: usize) -> Result<Option<NodeProperty<'a>>, ParsingError> {
+        let curr = self.blocks.skip_nops(curr)?;
 
-        curr_ptr = skip_nops(curr_ptr).unwrap();
-
-        let token = unsafe { Tokens::try_from(*curr_ptr).unwrap() };
+        let token = self.blocks.token_at(curr)?;
         if !matches!(token, Tokens::Property) {
-            return None;
+            return Ok(None);
         }
 
-        unsafe {
-            let length = { u32::from_be(*curr_ptr.add(1)) } as usize;
-
-            let name_offset = u32::from_be(*curr_ptr.add(2)) as isize;
-            let str_ptr = self.str_block_ptr.byte_offset(name_offset);
+        let length = self.blocks.u32_at(curr + 4)? as usize;
+        let name_offset = self.blocks.u32_at(curr + 8)? as usize;
+        let value = self.blocks.bytes_at(curr + 12, length)?;
+        let name = self.blocks.string_at(name_offset)?;
 
-            let value: &[u8] = {
-                let raw_slice_ref = slice_from_raw_parts(curr_ptr.add(3) as *const u8, length);
-                &*raw_slice_ref
-            };
+        self.curr = Some(curr + 12 + align_token(length));
 
-            curr_ptr = curr_ptr.add(3 + length.div_ceil(4));
-
-            self.curr = Some(curr_ptr);
+        Ok(Some(NodeProperty { name, value }))
+    }
+}
 
-            let name = CStr::from_ptr(str_ptr).to_str().unwrap();
+impl<'a> Iterator for PropertyIter<'a> {
+    type Item = Result<NodeProperty<'a>, ParsingError>;
 
-            Some(NodeProperty { name, value })
-        }
+    fn next(&mut self) -> Option<Self::Item> {
+        // `curr` stays empty after the last property or an error
+        let curr = self.curr.take()?;
+        self.parse_next(curr).transpose()
     }
 }
@@ -1,33 +1,25 @@
-use crate::tree::ParsingError;
+use crate::tree::ParsingErrorKind;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub enum Tokens {
-    BeginNode,
-    EndNode,
-    Property,
-    Nop,
-    End,
+    BeginNode = 1,
+    EndNode = 2,
+    Property = 3,
+    Nop = 4,
+    End = 9,
 }
 
 impl TryFrom<u32> for Tokens {
-    type Error = ParsingError;
+    type Error = ParsingErrorKind;
 
     fn try_from(value: u32) -> Result<Self, Self::Error> {
-        match u32::from_be(value) {
+        match value {
             1 => Ok(Self::BeginNode),
             2 => Ok(Self::EndNode),
             3 => Ok(Self::Property),
             4 => Ok(Self::Nop),
             9 => Ok(Self::End),
-            _ => Err(ParsingError::InvalidToken),
+            _ => Err(ParsingErrorKind::InvalidToken),
         }
     }
 }
-
-pub fn skip_nops(current: *const u32) -> Result<*const u32, ParsingError> {
-    let mut curr = current;
-    while matches!(unsafe { Tokens::try_from(*curr)? }, Tokens::Nop) {
-        curr = unsafe { curr.add(1) };
-    }
-    Ok(curr)
-}
@@ -0,0 +1,194 @@
+mod common;
+
+use common::{QEMU_VIRT_1HART, TRANSLATION, reader};
+use dtb_reader::Range;
+
+#[test]
+fn reg_uses_parent_cells() {
+    let dtb = reader(TRANSLATION);
+
+    let memory = dtb.find_node("/memory@80000000").unwrap();
+    assert_eq!(
+        memory.reg().collect::<Vec<_>>(),
+        [(0x8000_0000, 0x800_0000), (0x1_0000_0000, 0x1000_0000)]
+    );
+
+    let uart = dtb.find_node("/bus@40000000/uart@1000").unwrap();
+    assert_eq!(uart.reg().collect::<Vec<_>>(), [(0x1000, 0x100)]);
+}
+
+#[test]
+fn reg_ignores_partial_entry() {
+    let dtb = reader(TRANSLATION);
+    let device = dtb.find_node("/identity/device@12340000").unwrap();
+
+    assert_eq!(
+        device.reg().collect::<Vec<_>>(),
+        [(0x1234_0000, 0x1000), (0x1235_0000, 0x2000)]
+    );
+}
+
+#[test]
+fn reg_without_property() {
+    let dtb = reader(TRANSLATION);
+    let bus = dtb.find_node("/identity").unwrap();
+
+    assert_eq!(bus.reg().count(), 0);
+}
+
+#[test]
+fn cells_defaults() {
+    let dtb = reader(TRANSLATION);
+    let uart = dtb.find_node("/bus@40000000/uart@1000").unwrap();
+
+    assert_eq!((uart.address_cells(), uart.size_cells()), (2, 1));
+}
+
+#[test]
+fn cpu_reg_without_size() {
+    let dtb = reader(QEMU_VIRT_1HART);
+    let cpu = dtb.find_node("/cpus/cpu@0").unwrap();
+
+    assert_eq!(cpu.reg().collect::<Vec<_>>(), [(0, 0)]);
+}
+
+#[test]
+fn parent() {
+    let dtb = reader(TRANSLATION);
+    let device = dtb
+        .find_node("/bus@40000000/bridge@100000/device@10")
+        .unwrap();
+
+    let bridge = device.parent().unwrap();
+    assert_eq!(bridge.full_name(), "bridge@100000");
+    assert_eq!(bridge.parent().unwrap().full_name(), "bus@40000000");
+    assert_eq!(bridge.parent().unwrap().parent().unwrap().full_name(), "");
+    assert!(dtb.root_node().parent().is_none());
+}
+
+#[test]
+fn ranges() {
+    let dtb = reader(TRANSLATION);
+    let bus = dtb.find_node("/bus@40000000").unwrap();
+
+    assert_eq!(
+        bus.ranges().unwrap().collect::<Vec<_>>(),
+        [
+            Range {
+                child_address: 0,
+                parent_address: 0x4000_0000,
+                size: 0x1000_0000
+            },
+            Range {
+                child_address: 0x2000_0000,
+                parent_address: 0x1_0000_0000,
+                size: 0x100_0000
+            },
+        ]
+    );
+    assert_eq!(
+        bus.dma_ranges().unwrap().collect::<Vec<_>>(),
+        [Range {
+            child_address: 0x8000_0000,
+            parent_address: 0,
+            size: 0x8000_0000
+        }]
+    );
+
+    assert_eq!(
+        dtb.find_node("/identity")
+            .unwrap()
+            .ranges()
+            .unwrap()
+            .count(),
+        0
+    );
+    assert!(dtb.find_node("/memory").unwrap().ranges().is_none());
+}
+
+#[test]
+fn pci_ranges() {
+    let dtb = reader(QEMU_VIRT_1HART);
+    let pci = dtb.find_node("/soc/pci@30000000").unwrap();
+    let ranges: Vec<_> = pci.ranges().unwrap().collect();
+
+    // The low 64 bits of the 3 cells PCI addresses are kept
+    assert_eq!(ranges.len(), 3);
+    assert_eq!(
+        ranges[1],
+        Range {
+            child_address: 0x4000_0000,
+            parent_address: 0x4000_0000,
+            size: 0x4000_0000
+        }
+    );
+    assert_eq!(ranges[2].parent_address, 0x4_0000_0000);
+}
+
+#[test]
+fn range_translate() {
+    let range = Range {
+        child_address: 0x1000,
+        parent_address: 0x8000,
+        size: 0x100,
+    };
+
+    assert_eq!(range.translate(0x1000), Some(0x8000));
+    assert_eq!(range.translate(0x10ff), Some(0x80ff));
+    assert_eq!(range.translate(0x1100), None);
+    assert_eq!(range.translate(0xfff), None);
+}
+
+#[test]
+fn translate_address() {
+    let dtb = reader(TRANSLATION);
+    let translate = |path: &str| {
+        let node = dtb.find_node(path).unwrap();
+        let (address, _) = node.reg().next().unwrap();
+        node.translate_address(address)
+    };
+
+    assert_eq!(translate("/memory@80000000"), Some(0x8000_0000));
+    assert_eq!(translate("/bus@40000000/uart@1000"), Some(0x4000_1000));
+    assert_eq!(
+        translate("/bus@40000000/high@20000000"),
+        Some(0x1_0000_0800)
+    );
+    assert_eq!(
+        translate("/bus@40000000/bridge@100000/device@10"),
+        Some(0x4010_0010)
+    );
+    assert_eq!(translate("/identity/device@12340000"), Some(0x1234_0000));
+    // A bus without `ranges` is not mapped in the CPU address space
+    assert_eq!(translate("/bus@40000000/isolated@200000/device@0"), None);
+}
+
+#[test]
+fn translate_address_of_iterated_nodes() {
+    let dtb = reader(TRANSLATION);
+    let bus = dtb.find_node("/bus@40000000").unwrap();
+
+    let translated: Vec<_> = bus
+        .descendants()
+        .filter_map(|(_, node)| node.translate_address(node.reg().next()?.0))
+        .collect();
+    assert_eq!(translated, [0x4000_1000, 0x1_0000_0800, 0x4010_0010]);
+}
+
+#[test]
+fn translate_address_outside_ranges() {
+    let dtb = reader(TRANSLATION);
+    let uart = dtb.find_node("/bus@40000000/uart@1000").unwrap();
+
+    assert_eq!(uart.translate_address(0x1000_0000), None);
+}
+
+#[test]
+fn translate_address_qemu_serial() {
+    let dtb = reader(QEMU_VIRT_1HART);
+    let serial = dtb.find_node("serial0").unwrap();
+    let (address, size) = serial.reg().next().unwrap();
+
+    assert_eq!((address, size), (0x1000_0000, 0x100));
+    assert_eq!(serial.translate_address(address), Some(0x1000_0000));
+}
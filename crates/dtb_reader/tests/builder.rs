@@ -0,0 +1,228 @@
+mod common;
+
+use common::{HIFIVE_UNLEASHED, QEMU_VIRT_1HART, QEMU_VIRT_4HARTS, fixture, reader};
+use dtb_reader::{BuildError, DeviceTreeNode, DtbReader, FdtBuilder};
+
+fn synthetic() -> FdtBuilder {
+    let mut builder = FdtBuilder::new();
+    builder
+        .boot_cpuid_phys(1)
+        .reserve_memory(0x8000_0000, 0x20_0000)
+        .begin_node("")
+        .property_u32("#address-cells", 2)
+        .property_u32("#size-cells", 2)
+        .property_str_list("compatible", &["meos,synthetic", "meos,board"])
+        .begin_node("cpus")
+        .property_u32("#address-cells", 1)
+        .property_u32("#size-cells", 0)
+        .begin_node("cpu@0")
+        .property_str("device_type", "cpu")
+        .property_cells("reg", &[0])
+        .end_node()
+        .end_node()
+        .begin_node("memory@80000000")
+        .property_str("device_type", "memory")
+        .property_cells("reg", &[0, 0x8000_0000, 0, 0x800_0000])
+        .end_node()
+        .begin_node("chosen")
+        .property_str("bootargs", "console=ttyS0")
+        .property_u64("linux,initrd-start", 0x8800_0000)
+        .property_u64("linux,initrd-end", 0x8810_0000)
+        .property_empty("meos,dump-tree")
+        .end_node()
+        .end_node();
+    builder
+}
+
+fn leak(blob: Vec<u8>) -> &'static [u8] {
+    blob.leak()
+}
+
+#[test]
+fn synthetic_tree_round_trip() {
+    let blob = leak(synthetic().finish().unwrap());
+    let dtb = DtbReader::from_bytes(blob).unwrap();
+
+    assert_eq!(dtb.total_size(), blob.len());
+    assert_eq!(dtb.fdt_header.boot_cpuid_phys(), 1);
+    assert_eq!(
+        dtb.reserve_entry_iter()
+            .map(|e| (e.address(), e.size()))
+            .collect::<Vec<_>>(),
+        [(0x8000_0000, 0x20_0000)]
+    );
+
+    let root = dtb.root_node();
+    let compatible: Vec<_> = root
+        .get_property("compatible")
+        .unwrap()
+        .as_str_list()
+        .unwrap()
+        .collect();
+    assert_eq!(compatible, ["meos,synthetic", "meos,board"]);
+
+    let memory = dtb.find_node("/memory@80000000").unwrap();
+    assert_eq!(
+        memory.reg().collect::<Vec<_>>(),
+        [(0x8000_0000, 0x800_0000)]
+    );
+
+    let chosen = dtb.find_node("/chosen").unwrap();
+    assert_eq!(
+        chosen.get_property("bootargs").unwrap().value_str(),
+        Some("console=ttyS0")
+    );
+    assert_eq!(
+        chosen.get_property("linux,initrd-end").unwrap().as_u64(),
+        Ok(0x8810_0000)
+    );
+    assert_eq!(chosen.is_present("meos,dump-tree"), Ok(true));
+
+    let cpu = dtb.find_node("/cpus/cpu@0").unwrap();
+    assert_eq!(cpu.reg().collect::<Vec<_>>(), [(0, 0)]);
+}
+
+#[test]
+fn header_layout() {
+    let blob = synthetic().finish().unwrap();
+    let field =
+        |index: usize| u32::from_be_bytes(blob[index * 4..index * 4 + 4].try_into().unwrap());
+
+    assert_eq!(field(0), 0xd00d_feed);
+    assert_eq!(field(1) as usize, blob.len());
+    // Version 17, compatible with 16
+    assert_eq!((field(5), field(6)), (17, 16));
+    // Reservation map right after the header, then the structure and strings blocks
+    assert_eq!(field(4), 40);
+    assert_eq!(field(2), 40 + 2 * 16);
+    assert_eq!(field(3), field(2) + field(9));
+    assert_eq!(field(3) + field(8), field(1));
+    // Structure block ends with FDT_END
+    let end = field(3) as usize;
+    assert_eq!(&blob[end - 4..end], 9u32.to_be_bytes());
+}
+
+#[test]
+fn strings_are_deduplicated() {
+    let blob = synthetic().finish().unwrap();
+    let strings = u32::from_be_bytes(blob[12..16].try_into().unwrap()) as usize;
+    let strings = &blob[strings..];
+
+    let names: Vec<_> = strings
+        .split(|&b| b == 0)
+        .filter(|s| !s.is_empty())
+        .collect();
+    let count = |name: &[u8]| names.iter().filter(|&&n| n == name).count();
+    assert_eq!(count(b"#address-cells"), 1);
+    assert_eq!(count(b"device_type"), 1);
+    assert_eq!(count(b"reg"), 1);
+}
+
+fn assert_same_tree(a: &DeviceTreeNode, b: &DeviceTreeNode) {
+    assert_eq!(a.full_name(), b.full_name());
+
+    let props = |n: &DeviceTreeNode| {
+        n.properties()
+            .map(|p| (p.name().to_string(), p.raw_value().to_vec()))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(props(a), props(b), "{}", a.path());
+
+    assert_eq!(a.children().count(), b.children().count(), "{}", a.path());
+    for (a, b) in a.children().zip(b.children()) {
+        assert_same_tree(&a, &b);
+    }
+}
+
+#[test]
+fn copy_fixtures() {
+    for name in [QEMU_VIRT_1HART, QEMU_VIRT_4HARTS, HIFIVE_UNLEASHED] {
+        let original = reader(name);
+
+        let mut builder = FdtBuilder::new();
+        builder.boot_cpuid_phys(original.fdt_header.boot_cpuid_phys());
+        for entry in original.reserve_entry_iter() {
+            builder.reserve_memory(entry.address(), entry.size());
+        }
+        builder.copy_node(&original.root_node());
+
+        let blob = leak(builder.finish().unwrap());
+        let copy = DtbReader::from_bytes(blob).unwrap();
+
+        assert_same_tree(&original.root_node(), &copy.root_node());
+        assert!(original.reserve_entry_iter().eq(copy.reserve_entry_iter()));
+        // Same layout as dtc
+        assert_eq!(blob, fixture(name), "{name}");
+    }
+}
+
+#[test]
+fn patch_chosen() {
+    let original = reader(QEMU_VIRT_1HART);
+    let root = original.root_node();
+
+    let mut builder = FdtBuilder::new();
+    builder.begin_node("").copy_properties(&root);
+    for child in root.children() {
+        if child.name() == "chosen" {
+            builder
+                .begin_node("chosen")
+                .copy_properties(&child)
+                .property_str("bootargs", "quiet")
+                .end_node();
+        } else {
+            builder.copy_node(&child);
+        }
+    }
+    builder.end_node();
+
+    let blob = leak(builder.finish().unwrap());
+    let dtb = DtbReader::from_bytes(blob).unwrap();
+    let chosen = dtb.find_node("/chosen").unwrap();
+
+    assert_eq!(
+        chosen.get_property("bootargs").unwrap().value_str(),
+        Some("quiet")
+    );
+    assert_eq!(
+        chosen.get_property("stdout-path").unwrap().value_str(),
+        Some("/soc/serial@10000000")
+    );
+    assert_eq!(dtb.find_compatible("ns16550a").count(), 1);
+}
+
+#[test]
+fn misuse() {
+    assert_eq!(FdtBuilder::new().finish(), Err(BuildError::EmptyTree));
+    assert_eq!(
+        FdtBuilder::new().begin_node("").finish(),
+        Err(BuildError::UnclosedNodes(1))
+    );
+    assert_eq!(
+        FdtBuilder::new().end_node().finish(),
+        Err(BuildError::UnbalancedEndNode)
+    );
+    assert_eq!(
+        FdtBuilder::new()
+            .begin_node("")
+            .end_node()
+            .begin_node("")
+            .end_node()
+            .finish(),
+        Err(BuildError::MultipleRoots)
+    );
+    assert_eq!(
+        FdtBuilder::new().property_u32("a", 1).finish(),
+        Err(BuildError::PropertyOutsideNode)
+    );
+    assert_eq!(
+        FdtBuilder::new()
+            .begin_node("")
+            .begin_node("child")
+            .end_node()
+            .property_u32("late", 1)
+            .end_node()
+            .finish(),
+        Err(BuildError::PropertyAfterChild)
+    );
+}
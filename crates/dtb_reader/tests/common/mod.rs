@@ -0,0 +1,79 @@
+#![allow(dead_code)]
+
+use std::{fs, path::PathBuf};
+
+use dtb_reader::{DtbInitError, DtbReader};
+
+/// Loads `tests/fixtures/<name>` in a buffer that lives until the end of the tests
+pub fn fixture(name: &str) -> &'static [u8] {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name);
+    let bytes = fs::read(&path).unwrap_or_else(|e| panic!("{}: {e}", path.display()));
+
+    bytes.leak()
+}
+
+/// Copy of `bytes` with the header field at `index` (in words) replaced by `value`
+pub fn with_header_field(bytes: &[u8], index: usize, value: u32) -> &'static [u8] {
+    let mut bytes = bytes.to_vec();
+    bytes[index * 4..index * 4 + 4].copy_from_slice(&value.to_be_bytes());
+    bytes.leak()
+}
+
+pub fn try_reader(name: &str) -> Result<DtbReader<'static>, DtbInitError> {
+    DtbReader::from_bytes(fixture(name))
+}
+
+/// Root with `/cpus` and a chain of `depth` nested nodes named `node`, without properties
+pub fn nested(depth: usize) -> &'static [u8] {
+    const BEGIN_NODE: u32 = 1;
+    const END_NODE: u32 = 2;
+    const END: u32 = 9;
+    // Names padded with NULs to the next token
+    const CPUS: [u32; 2] = [u32::from_be_bytes(*b"cpus"), 0];
+    const NODE: [u32; 2] = [u32::from_be_bytes(*b"node"), 0];
+
+    let mut structure = vec![BEGIN_NODE, 0, BEGIN_NODE, CPUS[0], CPUS[1], END_NODE];
+    for _ in 0..depth {
+        structure.extend([BEGIN_NODE, NODE[0], NODE[1]]);
+    }
+    structure.extend(std::iter::repeat_n(END_NODE, depth + 1));
+    structure.push(END);
+
+    // Header, then the empty memory reservation map, then the structure block
+    let off_dt_struct = 40 + 16;
+    let size_dt_struct = structure.len() * 4;
+    let totalsize = off_dt_struct + size_dt_struct;
+    let header = [
+        0xd00dfeed,
+        totalsize as u32,
+        off_dt_struct as u32,
+        totalsize as u32,
+        40,
+        17,
+        16,
+        0,
+        0,
+        size_dt_struct as u32,
+    ];
+
+    header
+        .into_iter()
+        .chain([0; 4])
+        .chain(structure)
+        .flat_map(u32::to_be_bytes)
+        .collect::<Vec<_>>()
+        .leak()
+}
+
+pub fn reader(name: &str) -> DtbReader<'static> {
+    try_reader(name).unwrap_or_else(|e| panic!("{name}: {e:?}"))
+}
+
+pub const QEMU_VIRT_1HART: &str = "qemu-virt-1hart.dtb";
+pub const QEMU_VIRT_4HARTS: &str = "qemu-virt-4harts.dtb";
+pub const HIFIVE_UNLEASHED: &str = "sifive-hifive-unleashed-a00.dtb";
+pub const TRANSLATION: &str = "translation.dtb";
+pub const OVERLAY_BASE: &str = "overlay-base.dtb";
+pub const OVERLAY: &str = "overlay.dtbo";
@@ -0,0 +1,237 @@
+mod common;
+
+use common::{HIFIVE_UNLEASHED, OVERLAY_BASE, QEMU_VIRT_4HARTS, reader};
+use dtb_reader::{CpuPosition, DtbReader, FdtBuilder, IsaExtension, IsaExtensions, MmuType};
+
+/// Tree with a single hart with this `riscv,isa`
+fn isa_tree(isa: &str) -> Vec<u8> {
+    FdtBuilder::new()
+        .begin_node("")
+        .begin_node("cpus")
+        .property_u32("#address-cells", 1)
+        .property_u32("#size-cells", 0)
+        .begin_node("cpu@0")
+        .property_str("device_type", "cpu")
+        .property_u32("reg", 0)
+        .property_str("riscv,isa", isa)
+        .end_node()
+        .end_node()
+        .end_node()
+        .finish()
+        .unwrap()
+}
+
+#[test]
+fn harts() {
+    let dtb = reader(QEMU_VIRT_4HARTS);
+
+    let hart_ids: Vec<_> = dtb.cpus().map(|cpu| cpu.hart_id()).collect();
+    assert_eq!(hart_ids, [Some(0), Some(1), Some(2), Some(3)]);
+
+    let cpu = dtb.cpu_by_hart_id(2).unwrap();
+    assert_eq!(cpu.node().path().to_string(), "/cpus/cpu@2");
+    assert_eq!(cpu.status(), "okay");
+    assert!(cpu.is_enabled());
+    assert_eq!(cpu.mmu_type(), Some(MmuType::Sv57));
+    assert_eq!(cpu.xlen(), Some(64));
+    assert_eq!(cpu.interrupt_controller_phandle(), Some(6));
+    assert_eq!(
+        cpu.interrupt_controller().unwrap().path().to_string(),
+        "/cpus/cpu@2/interrupt-controller"
+    );
+
+    assert!(dtb.cpu_by_hart_id(4).is_none());
+}
+
+#[test]
+fn disabled_hart() {
+    let dtb = reader(HIFIVE_UNLEASHED);
+
+    let monitor = dtb.cpu_by_hart_id(0).unwrap();
+    assert_eq!(monitor.status(), "disabled");
+    assert!(!monitor.is_enabled());
+    assert_eq!(monitor.mmu_type(), None);
+    // Not in `cpu-map`
+    assert_eq!(monitor.position(), None);
+
+    assert_eq!(dtb.cpus().filter(|cpu| cpu.is_enabled()).count(), 4);
+    assert_eq!(
+        dtb.cpu_by_hart_id(1).unwrap().mmu_type(),
+        Some(MmuType::Sv39)
+    );
+}
+
+#[test]
+fn timebase_frequency() {
+    let dtb = reader(QEMU_VIRT_4HARTS);
+    assert_eq!(dtb.timebase_frequency(), Some(10_000_000));
+    // Inherited from `/cpus`
+    assert!(
+        dtb.cpus()
+            .all(|cpu| cpu.timebase_frequency() == Some(10_000_000))
+    );
+
+    let dtb = reader(HIFIVE_UNLEASHED);
+    assert_eq!(dtb.timebase_frequency(), Some(1_000_000));
+
+    let blob = FdtBuilder::new()
+        .begin_node("")
+        .begin_node("cpus")
+        .property_u64("timebase-frequency", 0x1_0000_0000)
+        .begin_node("cpu@0")
+        .property_str("device_type", "cpu")
+        .property_u32("timebase-frequency", 24_000_000)
+        .end_node()
+        .end_node()
+        .end_node()
+        .finish()
+        .unwrap();
+    let dtb = DtbReader::from_bytes(&blob).unwrap();
+    assert_eq!(dtb.timebase_frequency(), Some(0x1_0000_0000));
+    // The hart overrides `/cpus`
+    assert_eq!(
+        dtb.cpus().next().unwrap().timebase_frequency(),
+        Some(24_000_000)
+    );
+}
+
+#[test]
+fn isa_extensions_list() {
+    let dtb = reader(QEMU_VIRT_4HARTS);
+    let cpu = dtb.cpus().next().unwrap();
+
+    let extensions = cpu.extensions();
+    for extension in [
+        IsaExtension::I,
+        IsaExtension::M,
+        IsaExtension::A,
+        IsaExtension::C,
+        IsaExtension::H,
+        IsaExtension::Zicsr,
+        IsaExtension::Zifencei,
+        IsaExtension::Sstc,
+    ] {
+        assert!(extensions.contains(extension), "{extension:?}");
+    }
+    assert!(!extensions.contains(IsaExtension::V));
+
+    // Unknown to `IsaExtension`
+    assert!(cpu.has_extension("zic64b"));
+    assert!(!cpu.has_extension("zvl128b"));
+    assert_eq!(cpu.extension_names().count(), 39);
+}
+
+#[test]
+fn isa_string() {
+    let dtb = reader(OVERLAY_BASE);
+    let cpu = dtb.cpus().next().unwrap();
+
+    let names: Vec<_> = cpu.extension_names().collect();
+    assert_eq!(names, ["i", "m", "a", "c"]);
+    assert_eq!(cpu.extensions().to_string(), "imac");
+    assert_eq!(cpu.xlen(), Some(64));
+}
+
+#[test]
+fn isa_string_parsing() {
+    let cases: [(&str, &[&str]); 5] = [
+        (
+            "rv64imafdc_zicsr_zifencei",
+            &["i", "m", "a", "f", "d", "c", "zicsr", "zifencei"],
+        ),
+        (
+            "rv64gc",
+            &["i", "m", "a", "f", "d", "zicsr", "zifencei", "c"],
+        ),
+        // Versions are removed, `p` of `zicbop` is not one
+        (
+            "rv32i2p1m2p0_zicsr2p0_zicbop",
+            &["i", "m", "zicsr", "zicbop"],
+        ),
+        // Multi-letter extension without separator
+        ("rv64imaczicsr_sstc", &["i", "m", "a", "c", "zicsr", "sstc"]),
+        ("RV64IMA__ZBB", &["I", "M", "A", "ZBB"]),
+    ];
+
+    for (isa, expected) in cases {
+        let blob = isa_tree(isa);
+        let dtb = DtbReader::from_bytes(&blob).unwrap();
+        let cpu = dtb.cpus().next().unwrap();
+
+        let names: Vec<_> = cpu.extension_names().collect();
+        assert_eq!(names, expected, "{isa}");
+    }
+
+    let blob = isa_tree("rv32imac");
+    let dtb = DtbReader::from_bytes(&blob).unwrap();
+    assert_eq!(dtb.cpus().next().unwrap().xlen(), Some(32));
+}
+
+#[test]
+fn extension_set() {
+    let set: IsaExtensions = [IsaExtension::Zicsr, IsaExtension::I, IsaExtension::C]
+        .into_iter()
+        .collect();
+
+    assert_eq!(set.len(), 3);
+    assert_eq!(set.to_string(), "ic_zicsr");
+    assert!(set.contains_all([IsaExtension::I].into_iter().collect()));
+    assert!(!set.contains_all([IsaExtension::M].into_iter().collect()));
+
+    let multi: IsaExtensions = [IsaExtension::Zba, IsaExtension::Zbb].into_iter().collect();
+    assert_eq!(multi.to_string(), "zba_zbb");
+
+    assert_eq!(IsaExtension::from_name("ZICSR"), Some(IsaExtension::Zicsr));
+    assert_eq!(IsaExtension::from_name("zic64b"), None);
+    assert!(IsaExtensions::new().is_empty());
+}
+
+#[test]
+fn cpu_map() {
+    let dtb = reader(HIFIVE_UNLEASHED);
+
+    let positions: Vec<_> = dtb.cpus().filter_map(|cpu| cpu.position()).collect();
+    let expected: Vec<_> = (0..4)
+        .map(|core| CpuPosition {
+            socket: None,
+            cluster: Some(0),
+            core,
+            thread: None,
+        })
+        .collect();
+    assert_eq!(positions, expected);
+
+    let blob = FdtBuilder::new()
+        .begin_node("")
+        .begin_node("cpus")
+        .begin_node("cpu@0")
+        .property_str("device_type", "cpu")
+        .property_u32("phandle", 1)
+        .end_node()
+        .begin_node("cpu-map")
+        .begin_node("socket1")
+        .begin_node("cluster2")
+        .begin_node("core3")
+        .begin_node("thread1")
+        .property_u32("cpu", 1)
+        .end_node()
+        .end_node()
+        .end_node()
+        .end_node()
+        .end_node()
+        .end_node()
+        .end_node()
+        .finish()
+        .unwrap();
+    let dtb = DtbReader::from_bytes(&blob).unwrap();
+
+    assert_eq!(
+        dtb.cpus().next().unwrap().position(),
+        Some(CpuPosition {
+            socket: Some(1),
+            cluster: Some(2),
+            core: 3,
+            thread: Some(1),
+        })
+    );
+}
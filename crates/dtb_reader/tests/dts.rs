@@ -0,0 +1,143 @@
+mod common;
+
+use common::{QEMU_VIRT_1HART, reader};
+use dtb_reader::{DtbReader, FdtBuilder};
+
+#[test]
+fn dump_subtree() {
+    let dtb = reader(QEMU_VIRT_1HART);
+    let uart = dtb.find_node("/soc/serial@10000000").unwrap();
+
+    let expected = "serial@10000000 {
+\tinterrupts = <0x0a>;
+\tinterrupt-parent = <&{/soc/plic@c000000}>;
+\tclock-frequency = <0x384000>;
+\treg = <0x00 0x10000000 0x00 0x100>;
+\tcompatible = \"ns16550a\";
+};
+";
+    assert_eq!(uart.dts(&dtb).to_string(), expected);
+
+    let mut dumped = String::new();
+    uart.dump(&dtb, &mut dumped).unwrap();
+    assert_eq!(dumped, expected);
+}
+
+#[test]
+fn dump_whole_tree() {
+    let dtb = reader(QEMU_VIRT_1HART);
+
+    let mut dts = String::new();
+    dtb.dump(&mut dts).unwrap();
+
+    assert!(dts.starts_with("/dts-v1/;\n\n/ {\n\t#address-cells = <0x02>;\n"));
+    assert!(dts.ends_with("\t};\n};\n"));
+    assert!(dts.contains("\n\t\tcpu-map {\n\t\t\tcluster0 {\n\t\t\t\tcore0 {\n"));
+    assert!(dts.contains("\t\t\t\t\tcpu = <&{/cpus/cpu@0}>;\n"));
+    assert!(dts.contains(
+        "\t\tinterrupts-extended = <&{/cpus/cpu@0/interrupt-controller} 0x0b>, \
+         <&{/cpus/cpu@0/interrupt-controller} 0x09>;\n"
+    ));
+    assert!(dts.contains("\t\tcompatible = \"sifive,plic-1.0.0\", \"riscv,plic0\";\n"));
+    assert!(dts.contains("\t\tinterrupt-controller;\n"));
+
+    // Every node is opened and closed
+    assert_eq!(dts.matches(" {\n").count(), dts.matches("};\n").count());
+    assert_eq!(
+        dts.matches(" {\n").count(),
+        dtb.root_node().descendants().count() + 1
+    );
+}
+
+#[test]
+fn dump_memory_reservations() {
+    let blob = FdtBuilder::new()
+        .reserve_memory(0x8000_0000, 0x20_0000)
+        .begin_node("")
+        .begin_node("cpus")
+        .end_node()
+        .end_node()
+        .finish()
+        .unwrap();
+    let dtb = DtbReader::from_bytes(&blob).unwrap();
+
+    let mut dts = String::new();
+    dtb.dump(&mut dts).unwrap();
+    assert_eq!(
+        dts,
+        "/dts-v1/;\n/memreserve/ 0x80000000 0x200000;\n\n/ {\n\tcpus {\n\t};\n};\n"
+    );
+}
+
+#[test]
+fn value_heuristics() {
+    let blob = FdtBuilder::new()
+        .begin_node("")
+        .begin_node("cpus")
+        .end_node()
+        .begin_node("node")
+        .property_empty("empty")
+        .property_str("quoted", "a \"b\" \\c")
+        .property_str_list("list", &["one", "two"])
+        .property("bytes", &[0xde, 0xad, 0xbe])
+        .property("binary", &[0x01, 0x02, 0x03, 0x00])
+        .property("empty-string", &[0])
+        .property_u64("u64", 0x1_0000_0002)
+        // Unknown phandle, written as cells
+        .property_cells("clocks", &[0x42, 1])
+        .end_node()
+        .end_node()
+        .finish()
+        .unwrap();
+    let dtb = DtbReader::from_bytes(&blob).unwrap();
+    let node = dtb.find_node("/node").unwrap();
+
+    let expected = "node {
+\tempty;
+\tquoted = \"a \\\"b\\\" \\\\c\";
+\tlist = \"one\", \"two\";
+\tbytes = [de ad be];
+\tbinary = <0x1020300>;
+\tempty-string = [00];
+\tu64 = <0x01 0x02>;
+\tclocks = <0x42 0x01>;
+};
+";
+    assert_eq!(node.dts(&dtb).to_string(), expected);
+}
+
+#[test]
+fn dump_phandle_lists() {
+    let blob = FdtBuilder::new()
+        .begin_node("")
+        .begin_node("cpus")
+        .end_node()
+        .begin_node("clock")
+        .property_u32("phandle", 1)
+        .property_u32("#clock-cells", 1)
+        .end_node()
+        .begin_node("gpio")
+        .property_u32("phandle", 2)
+        .property_u32("#gpio-cells", 2)
+        .end_node()
+        .begin_node("device")
+        // Empty entry between the two clocks
+        .property_cells("clocks", &[1, 5, 0, 1, 6])
+        .property_cells("reset-gpios", &[2, 3, 0])
+        // Truncated entry, written as cells
+        .property_cells("enable-gpios", &[2, 3])
+        .end_node()
+        .end_node()
+        .finish()
+        .unwrap();
+    let dtb = DtbReader::from_bytes(&blob).unwrap();
+    let device = dtb.find_node("/device").unwrap();
+
+    let expected = "device {
+\tclocks = <&{/clock} 0x05>, <0x00>, <&{/clock} 0x06>;
+\treset-gpios = <&{/gpio} 0x03 0x00>;
+\tenable-gpios = <0x02 0x03>;
+};
+";
+    assert_eq!(device.dts(&dtb).to_string(), expected);
+}
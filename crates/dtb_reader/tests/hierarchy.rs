@@ -0,0 +1,136 @@
+mod common;
+
+use common::{HIFIVE_UNLEASHED, QEMU_VIRT_1HART, TRANSLATION, nested, reader};
+use dtb_reader::{DtbReader, MAX_DEPTH};
+
+#[test]
+fn path() {
+    let dtb = reader(QEMU_VIRT_1HART);
+
+    assert_eq!(dtb.root_node().path().to_string(), "/");
+    assert_eq!(
+        dtb.find_node("serial0").unwrap().path().to_string(),
+        "/soc/serial@10000000"
+    );
+    assert_eq!(
+        dtb.find_node("/cpus/cpu@0/interrupt-controller")
+            .unwrap()
+            .path()
+            .to_string(),
+        "/cpus/cpu@0/interrupt-controller"
+    );
+}
+
+#[test]
+fn write_path() {
+    let dtb = reader(TRANSLATION);
+    let device = dtb
+        .find_node("/bus@40000000/bridge@100000/device@10")
+        .unwrap();
+
+    let mut path = String::new();
+    device.write_path(&mut path).unwrap();
+    assert_eq!(path, "/bus@40000000/bridge@100000/device@10");
+}
+
+#[test]
+fn depth_and_ancestors() {
+    let dtb = reader(TRANSLATION);
+    let device = dtb
+        .find_node("/bus@40000000/bridge@100000/device@10")
+        .unwrap();
+
+    assert_eq!(dtb.root_node().depth(), 0);
+    assert_eq!(device.depth(), 3);
+    assert_eq!(
+        device
+            .ancestors()
+            .map(|a| a.full_name())
+            .collect::<Vec<_>>(),
+        ["bridge@100000", "bus@40000000", ""]
+    );
+    assert_eq!(dtb.root_node().ancestors().count(), 0);
+}
+
+#[test]
+fn descendants_pre_order() {
+    let dtb = reader(TRANSLATION);
+    let bus = dtb.find_node("/bus@40000000").unwrap();
+
+    assert_eq!(
+        bus.descendants()
+            .map(|(depth, n)| (depth, n.full_name()))
+            .collect::<Vec<_>>(),
+        [
+            (1, "uart@1000"),
+            (1, "high@20000000"),
+            (1, "bridge@100000"),
+            (2, "device@10"),
+            (1, "isolated@200000"),
+            (2, "device@0"),
+        ]
+    );
+}
+
+#[test]
+fn descendants_of_root_cover_tree() {
+    let dtb = reader(HIFIVE_UNLEASHED);
+    let root = dtb.root_node();
+
+    for (depth, node) in root.descendants() {
+        assert_eq!(node.depth(), depth, "{}", node.path());
+    }
+
+    fn count(node: &dtb_reader::DeviceTreeNode) -> usize {
+        node.children().map(|c| 1 + count(&c)).sum()
+    }
+    assert_eq!(root.descendants().count(), count(&root));
+}
+
+#[test]
+fn descendants_of_leaf() {
+    let dtb = reader(QEMU_VIRT_1HART);
+
+    assert_eq!(dtb.find_node("serial0").unwrap().descendants().count(), 0);
+}
+
+#[test]
+fn inherited_property() {
+    let dtb = reader(QEMU_VIRT_1HART);
+    let serial = dtb.find_node("serial0").unwrap();
+
+    // Defined by the node
+    let parent = serial.inherited_property("interrupt-parent").unwrap();
+    assert_eq!(parent.raw_value(), [0, 0, 0, 3]);
+
+    // Defined by `/cpus`
+    let cpu = dtb.find_node("/cpus/cpu@0").unwrap();
+    assert!(cpu.get_property("timebase-frequency").is_none());
+    let timebase = cpu.inherited_property("timebase-frequency").unwrap();
+    assert_eq!(timebase.raw_value(), 10_000_000u32.to_be_bytes());
+
+    assert!(serial.inherited_property("missing").is_none());
+}
+
+#[test]
+fn deepest_node() {
+    let dtb = DtbReader::from_bytes(nested(MAX_DEPTH)).unwrap();
+    let (depth, deepest) = dtb.root_node().descendants().last().unwrap();
+
+    assert_eq!(depth, MAX_DEPTH);
+    assert_eq!(deepest.depth(), MAX_DEPTH);
+    assert_eq!(deepest.ancestors().count(), MAX_DEPTH);
+    assert_eq!(deepest.path().to_string(), "/node".repeat(MAX_DEPTH));
+    assert_eq!(deepest.parent().unwrap().depth(), MAX_DEPTH - 1);
+}
+
+#[test]
+fn parent_of_node_by_phandle() {
+    let dtb = reader(QEMU_VIRT_1HART);
+    let intc = dtb.find_node("/cpus/cpu@0/interrupt-controller").unwrap();
+    let node = dtb.node_by_phandle(intc.phandle().unwrap()).unwrap();
+
+    assert_eq!(node.path().to_string(), "/cpus/cpu@0/interrupt-controller");
+    assert_eq!(node.parent().unwrap().full_name(), "cpu@0");
+    assert_eq!(node.depth(), 3);
+}
@@ -0,0 +1,218 @@
+//! Blobs derived from `qemu-virt-1hart.dtb`:
+//! - `bad-magic.dtb`: magic changed to `0xd00dfeef`
+//! - `unsupported-version.dtb`: version and last compatible version set to 16
+//! - `truncated-header.dtb`: first 20 bytes only
+//! - `truncated-struct.dtb`: cut in the middle of the structure block
+//! - `invalid-token.dtb`: first property token of the root replaced by `7`
+
+mod common;
+
+use common::{QEMU_VIRT_1HART, fixture, nested, try_reader, with_header_field};
+use dtb_reader::{DtbInitError, DtbReader, MAX_DEPTH, ParsingError, ParsingErrorKind};
+
+// Word index of the header fields
+const TOTALSIZE: usize = 1;
+const OFF_DT_STRUCT: usize = 2;
+const OFF_DT_STRINGS: usize = 3;
+const OFF_MEM_RSVMAP: usize = 4;
+const SIZE_DT_STRINGS: usize = 8;
+const SIZE_DT_STRUCT: usize = 9;
+
+#[test]
+fn bad_magic() {
+    assert!(matches!(
+        try_reader("bad-magic.dtb"),
+        Err(DtbInitError::InvalidHeader {
+            expected: 0xd00dfeed,
+            found: 0xd00dfeef
+        })
+    ));
+}
+
+#[test]
+fn unsupported_version() {
+    assert!(matches!(
+        try_reader("unsupported-version.dtb"),
+        Err(DtbInitError::UnsupportedDtbVersion)
+    ));
+}
+
+#[test]
+fn truncated_header() {
+    assert!(matches!(
+        try_reader("truncated-header.dtb"),
+        Err(DtbInitError::Truncated {
+            expected: 40,
+            found: 20
+        })
+    ));
+}
+
+#[test]
+fn truncated_struct() {
+    let size = fixture("truncated-struct.dtb").len();
+    let totalsize = fixture(QEMU_VIRT_1HART).len();
+
+    match try_reader("truncated-struct.dtb") {
+        Err(DtbInitError::Truncated { expected, found }) => {
+            assert_eq!((expected, found), (totalsize, size));
+        }
+        _ => panic!("truncated blob accepted"),
+    }
+}
+
+fn header_field(blob: &[u8], index: usize) -> usize {
+    u32::from_be_bytes(blob[index * 4..index * 4 + 4].try_into().unwrap()) as usize
+}
+
+fn parsing_error(blob: &[u8]) -> ParsingError {
+    match DtbReader::from_bytes(blob) {
+        Err(DtbInitError::ParsingError(e)) => e,
+        Err(e) => panic!("unexpected error {e:?}"),
+        Ok(_) => panic!("malformed blob accepted"),
+    }
+}
+
+#[test]
+fn invalid_token() {
+    let blob = fixture("invalid-token.dtb");
+    let error = parsing_error(blob);
+
+    assert!(matches!(error.kind, ParsingErrorKind::InvalidToken));
+    // BeginNode and the empty name of the root come before
+    assert_eq!(error.offset, header_field(blob, OFF_DT_STRUCT) + 8);
+}
+
+#[test]
+fn invalid_utf8_in_nested_node_name() {
+    let mut bytes = fixture(QEMU_VIRT_1HART).to_vec();
+    let start = header_field(&bytes, OFF_DT_STRUCT);
+    let name = bytes[start..]
+        .windows(5)
+        .position(|w| w == b"cpus\0")
+        .unwrap()
+        + start;
+    bytes[name + 2] = 0xff;
+
+    let error = parsing_error(&bytes);
+    assert!(matches!(
+        error.kind,
+        ParsingErrorKind::InvalidUtf8NodeName(_)
+    ));
+    assert_eq!(error.offset, name + 2);
+}
+
+#[test]
+fn property_name_out_of_strings_block() {
+    let mut bytes = fixture(QEMU_VIRT_1HART).to_vec();
+    let size_dt_strings = header_field(&bytes, SIZE_DT_STRINGS);
+    let strings = header_field(&bytes, OFF_DT_STRINGS);
+
+    // Name offset of the first property of the root
+    let nameoff = header_field(&bytes, OFF_DT_STRUCT) + 16;
+    bytes[nameoff..nameoff + 4].copy_from_slice(&(size_dt_strings as u32).to_be_bytes());
+
+    let error = parsing_error(&bytes);
+    assert!(matches!(error.kind, ParsingErrorKind::OutOfBounds));
+    assert_eq!(error.offset, strings + size_dt_strings);
+}
+
+#[test]
+fn fallible_iterators_of_valid_tree() {
+    let dtb = common::reader(QEMU_VIRT_1HART);
+    let root = dtb.root_node();
+
+    assert!(root.try_properties().all(|p| p.is_ok()));
+    assert_eq!(root.try_children().count(), root.children().count());
+    assert!(root.try_children().all(|c| c.is_ok()));
+}
+
+#[test]
+fn block_out_of_bounds() {
+    let blob = fixture(QEMU_VIRT_1HART);
+    let totalsize = blob.len() as u32;
+
+    for (field, value) in [
+        (OFF_DT_STRUCT, totalsize),
+        (SIZE_DT_STRUCT, totalsize),
+        (OFF_DT_STRINGS, totalsize + 4),
+        (OFF_DT_STRUCT, 0),
+        (OFF_MEM_RSVMAP, totalsize + 8),
+    ] {
+        assert!(
+            matches!(
+                DtbReader::from_bytes(with_header_field(blob, field, value)),
+                Err(DtbInitError::BlockOutOfBounds)
+            ),
+            "field {field} set to {value:#x}"
+        );
+    }
+}
+
+#[test]
+fn misaligned_block() {
+    let blob = fixture(QEMU_VIRT_1HART);
+
+    for (field, value) in [
+        (OFF_DT_STRUCT, 0x3a),
+        (OFF_MEM_RSVMAP, 0x2c),
+        (SIZE_DT_STRUCT, 0x102),
+    ] {
+        assert!(
+            matches!(
+                DtbReader::from_bytes(with_header_field(blob, field, value)),
+                Err(DtbInitError::MisalignedBlock)
+            ),
+            "field {field} set to {value:#x}"
+        );
+    }
+}
+
+#[test]
+fn structure_block_is_bounded() {
+    let blob = fixture(QEMU_VIRT_1HART);
+    let size_dt_struct = u32::from_be_bytes(blob[36..40].try_into().unwrap());
+
+    // The tree is parsed from the structure block only, even if the blob goes on
+    let shortened = with_header_field(blob, SIZE_DT_STRUCT, size_dt_struct - 16);
+    let error = parsing_error(shortened);
+    assert!(matches!(error.kind, ParsingErrorKind::OutOfBounds));
+    assert!(error.offset >= header_field(blob, OFF_DT_STRUCT) + size_dt_struct as usize - 16);
+}
+
+#[test]
+fn trailing_bytes_after_totalsize() {
+    let mut bytes = fixture(QEMU_VIRT_1HART).to_vec();
+    bytes.extend_from_slice(&[0xff; 64]);
+
+    let dtb = DtbReader::from_bytes(&bytes).unwrap();
+    assert_eq!(dtb.total_size(), bytes.len() - 64);
+}
+
+#[test]
+fn shrunk_totalsize() {
+    let blob = fixture(QEMU_VIRT_1HART);
+
+    assert!(matches!(
+        DtbReader::from_bytes(with_header_field(blob, TOTALSIZE, 0x100)),
+        Err(DtbInitError::BlockOutOfBounds)
+    ));
+}
+
+#[test]
+fn too_deep() {
+    assert!(DtbReader::from_bytes(nested(MAX_DEPTH)).is_ok());
+
+    let blob = nested(MAX_DEPTH + 1);
+    let error = parsing_error(blob);
+    assert!(matches!(error.kind, ParsingErrorKind::TooDeep));
+    // The root takes 8 bytes and `/cpus` 16, then every `node` 12
+    let deepest = header_field(blob, OFF_DT_STRUCT) + 24 + MAX_DEPTH * 12;
+    assert_eq!(error.offset, deepest);
+}
+
+#[test]
+fn very_deep_tree_does_not_overflow_the_stack() {
+    let error = parsing_error(nested(1_000_000));
+    assert!(matches!(error.kind, ParsingErrorKind::TooDeep));
+}
@@ -0,0 +1,134 @@
+mod common;
+
+use std::collections::HashSet;
+
+use common::{OVERLAY, OVERLAY_BASE, QEMU_VIRT_1HART, fixture, reader};
+use dtb_reader::{DtbReader, Overlay, OverlayError, apply_overlay};
+
+fn merged() -> DtbReader<'static> {
+    let base = reader(OVERLAY_BASE);
+    let overlay = Overlay::from_bytes(fixture(OVERLAY)).unwrap();
+
+    let blob = apply_overlay(&base, &overlay).unwrap();
+    DtbReader::from_bytes(blob.leak()).unwrap()
+}
+
+#[test]
+fn overlay_without_cpus() {
+    let overlay = Overlay::from_bytes(fixture(OVERLAY)).unwrap();
+
+    let fragments = overlay
+        .root_node()
+        .children()
+        .filter(|c| c.name() == "fragment");
+    assert_eq!(fragments.count(), 4);
+    assert_eq!(overlay.as_bytes().len(), fixture(OVERLAY).len());
+}
+
+#[test]
+fn adds_nodes_to_phandle_target() {
+    let dtb = merged();
+
+    let uarts: Vec<_> = dtb
+        .find_compatible("ns16550a")
+        .map(|n| n.path().to_string())
+        .collect();
+    assert_eq!(uarts, ["/soc/serial@10000000", "/soc/serial@10001000"]);
+
+    let uart1 = dtb.find_node("/soc/serial@10001000").unwrap();
+    let plic = uart1.interrupt_parent(&dtb).unwrap();
+    assert_eq!(plic.path().to_string(), "/soc/plic@c000000");
+
+    let clock = uart1.reference(&dtb, "clocks").unwrap();
+    assert_eq!(clock.path().to_string(), "/soc/clock-uart");
+    assert_eq!(
+        clock.get_property("clock-frequency").unwrap().as_u32(),
+        Ok(3686400)
+    );
+}
+
+#[test]
+fn merges_properties() {
+    let dtb = merged();
+
+    let rtc = dtb.find_node("/soc/rtc@101000").unwrap();
+    assert!(rtc.is_enabled());
+    // Untouched properties are kept
+    assert_eq!(rtc.get_property("interrupts").unwrap().as_u32(), Ok(11));
+
+    let chosen = dtb.find_node("/chosen").unwrap();
+    assert_eq!(
+        chosen.get_property("bootargs").unwrap().as_str(),
+        Ok("console=uart1")
+    );
+    assert_eq!(
+        chosen.get_property("stdout-path").unwrap().as_str(),
+        Ok("/soc/serial@10001000")
+    );
+}
+
+#[test]
+fn resolves_local_references() {
+    let dtb = merged();
+
+    let extra = dtb.find_node("/extra").unwrap();
+    let uart = extra.reference(&dtb, "uart").unwrap();
+    assert_eq!(uart.path().to_string(), "/soc/serial@10001000");
+}
+
+#[test]
+fn phandles_are_unique() {
+    let dtb = merged();
+
+    let mut phandles = HashSet::new();
+    for (_, node) in dtb.root_node().descendants() {
+        if let Some(phandle) = node.phandle() {
+            assert!(phandles.insert(phandle), "duplicate phandle {phandle}");
+            let found = dtb.node_by_phandle(phandle).unwrap();
+            assert_eq!(found.path().to_string(), node.path().to_string());
+        }
+    }
+}
+
+#[test]
+fn merges_symbols() {
+    let dtb = merged();
+
+    let symbols = dtb.find_node("/__symbols__").unwrap();
+    let symbol = |label| symbols.get_property(label).unwrap().as_str().unwrap();
+    assert_eq!(symbol("uart1"), "/soc/serial@10001000");
+    assert_eq!(symbol("uart_clk"), "/soc/clock-uart");
+    assert_eq!(symbol("uart0"), "/soc/serial@10000000");
+
+    // Fixup metadata is not copied in the merged tree
+    assert!(dtb.find_node("/__fixups__").is_none());
+    assert!(dtb.find_node("/__local_fixups__").is_none());
+    assert!(dtb.find_node("/fragment@0").is_none());
+}
+
+#[test]
+fn keeps_header_and_reserve_map() {
+    let base = reader(OVERLAY_BASE);
+    let dtb = merged();
+
+    assert_eq!(
+        dtb.fdt_header.boot_cpuid_phys(),
+        base.fdt_header.boot_cpuid_phys()
+    );
+    assert_eq!(
+        dtb.reserve_entry_iter().count(),
+        base.reserve_entry_iter().count()
+    );
+}
+
+#[test]
+fn unknown_symbol() {
+    // No `__symbols__` in the base tree
+    let base = reader(QEMU_VIRT_1HART);
+    let overlay = Overlay::from_bytes(fixture(OVERLAY)).unwrap();
+
+    assert!(matches!(
+        apply_overlay(&base, &overlay),
+        Err(OverlayError::UnknownSymbol(_))
+    ));
+}
@@ -0,0 +1,136 @@
+mod common;
+
+use common::{HIFIVE_UNLEASHED, QEMU_VIRT_1HART, reader};
+use dtb_reader::PhandleIndex;
+
+#[test]
+fn node_by_phandle() {
+    let dtb = reader(QEMU_VIRT_1HART);
+
+    let intc = dtb.node_by_phandle(2).unwrap();
+    assert_eq!(intc.path().to_string(), "/cpus/cpu@0/interrupt-controller");
+    assert_eq!(intc.phandle(), Some(2));
+
+    let plic = dtb.node_by_phandle(3).unwrap();
+    assert_eq!(plic.name(), "plic");
+
+    assert!(dtb.node_by_phandle(0).is_none());
+    assert!(dtb.node_by_phandle(u32::MAX).is_none());
+    assert!(dtb.node_by_phandle(0x1000).is_none());
+}
+
+#[test]
+fn node_by_phandle_with_index() {
+    let dtb = reader(QEMU_VIRT_1HART);
+    let index = PhandleIndex::new(&dtb);
+    let dtb = dtb.with_phandle_index(&index);
+
+    let intc = dtb.node_by_phandle(2).unwrap();
+    assert_eq!(intc.path().to_string(), "/cpus/cpu@0/interrupt-controller");
+    assert!(dtb.node_by_phandle(0x1000).is_none());
+}
+
+#[test]
+#[should_panic(expected = "phandle index of another blob")]
+fn phandle_index_of_another_blob() {
+    let index = PhandleIndex::new(&reader(HIFIVE_UNLEASHED));
+
+    reader(QEMU_VIRT_1HART).with_phandle_index(&index);
+}
+
+#[test]
+fn every_phandle_is_indexed() {
+    let dtb = reader(HIFIVE_UNLEASHED);
+    let index = PhandleIndex::new(&dtb);
+    let dtb = dtb.with_phandle_index(&index);
+
+    let mut count = 0;
+    for (_, node) in dtb.root_node().descendants() {
+        if let Some(phandle) = node.phandle() {
+            let found = dtb.node_by_phandle(phandle).unwrap();
+            assert_eq!(found.path().to_string(), node.path().to_string());
+            count += 1;
+        }
+    }
+    assert_eq!(count, 15);
+}
+
+#[test]
+fn reference() {
+    let dtb = reader(QEMU_VIRT_1HART);
+    let poweroff = dtb.find_node("/poweroff").unwrap();
+
+    let syscon = poweroff.reference(&dtb, "regmap").unwrap();
+    assert_eq!(syscon.full_name(), "test@100000");
+    assert!(poweroff.reference(&dtb, "compatible").is_none());
+}
+
+#[test]
+fn interrupt_parent() {
+    let dtb = reader(HIFIVE_UNLEASHED);
+
+    let uart = dtb.find_node("serial0").unwrap();
+    let plic = uart.interrupt_parent(&dtb).unwrap();
+    assert_eq!(plic.full_name(), "interrupt-controller@c000000");
+
+    assert!(
+        dtb.find_node("/cpus")
+            .unwrap()
+            .interrupt_parent(&dtb)
+            .is_none()
+    );
+}
+
+#[test]
+fn phandle_list_with_arguments() {
+    let dtb = reader(HIFIVE_UNLEASHED);
+    let plic = dtb.find_node("/soc/interrupt-controller@c000000").unwrap();
+
+    let entries: Vec<_> = plic
+        .phandle_list(&dtb, "interrupts-extended", "#interrupt-cells")
+        .map(|(node, args)| {
+            (
+                node.parent().unwrap().full_name(),
+                args.iter().collect::<Vec<_>>(),
+            )
+        })
+        .collect();
+
+    assert_eq!(entries.len(), 9);
+    assert_eq!(entries[0], ("cpu@0", vec![0xffff_ffff]));
+    assert_eq!(entries[2], ("cpu@1", vec![9]));
+    assert_eq!(entries[8], ("cpu@4", vec![9]));
+}
+
+#[test]
+fn phandle_list_without_cells() {
+    let dtb = reader(HIFIVE_UNLEASHED);
+    let prci = dtb.find_node("/soc/clock-controller@10000000").unwrap();
+
+    let clocks: Vec<_> = prci
+        .phandle_list(&dtb, "clocks", "#clock-cells")
+        .map(|(node, args)| (node.name(), args.len()))
+        .collect();
+    assert_eq!(clocks, [("hfclk", 0), ("rtcclk", 0)]);
+
+    assert_eq!(
+        prci.phandle_list(&dtb, "missing", "#clock-cells").count(),
+        0
+    );
+}
+
+#[test]
+fn clock_by_name() {
+    let dtb = reader(HIFIVE_UNLEASHED);
+    let eth = dtb.find_node("ethernet0").unwrap();
+
+    let (clock, args) = eth.clock_by_name(&dtb, "hclk").unwrap();
+    assert_eq!(clock.full_name(), "clock-controller@10000000");
+    assert_eq!(args.get(0), Some(2));
+    assert_eq!(args.get(1), None);
+
+    assert!(eth.clock_by_name(&dtb, "tx").is_none());
+    // No `clock-names`
+    let uart = dtb.find_node("serial0").unwrap();
+    assert!(uart.clock_by_name(&dtb, "baud").is_none());
+}
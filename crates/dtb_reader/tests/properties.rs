@@ -0,0 +1,154 @@
+mod common;
+
+use common::{HIFIVE_UNLEASHED, QEMU_VIRT_1HART, TRANSLATION, reader};
+use dtb_reader::{DeviceTreeNode, NodeProperty, PropertyError};
+
+fn property<'a>(node: &DeviceTreeNode<'a>, name: &str) -> NodeProperty<'a> {
+    node.get_property(name).unwrap()
+}
+
+#[test]
+fn as_str() {
+    let dtb = reader(QEMU_VIRT_1HART);
+    let test = dtb.find_node("/soc/test").unwrap();
+
+    assert_eq!(
+        property(&dtb.root_node(), "model").as_str(),
+        Ok("riscv-virtio,qemu")
+    );
+    // Bytes after the first NUL
+    assert_eq!(
+        property(&test, "compatible").as_str(),
+        Err(PropertyError::InvalidString)
+    );
+    // No NUL at all
+    assert_eq!(
+        property(&test, "reg").as_str(),
+        Err(PropertyError::InvalidString)
+    );
+}
+
+#[test]
+fn as_str_list() {
+    let dtb = reader(QEMU_VIRT_1HART);
+    let test = dtb.find_node("/soc/test").unwrap();
+
+    let compatible: Vec<_> = property(&test, "compatible")
+        .as_str_list()
+        .unwrap()
+        .collect();
+    assert_eq!(compatible, ["sifive,test1", "sifive,test0", "syscon"]);
+
+    let model: Vec<_> = property(&dtb.root_node(), "model")
+        .as_str_list()
+        .unwrap()
+        .collect();
+    assert_eq!(model, ["riscv-virtio,qemu"]);
+
+    let plic = dtb.find_node("/soc/plic").unwrap();
+    assert!(matches!(
+        property(&plic, "interrupt-controller").as_str_list(),
+        Err(PropertyError::InvalidString)
+    ));
+}
+
+#[test]
+fn as_u32() {
+    let dtb = reader(QEMU_VIRT_1HART);
+    let cpus = dtb.cpus_node();
+
+    assert_eq!(
+        property(&cpus, "timebase-frequency").as_u32(),
+        Ok(10_000_000)
+    );
+    assert_eq!(
+        property(&dtb.find_node("/soc/test").unwrap(), "reg").as_u32(),
+        Err(PropertyError::InvalidLength {
+            expected: 4,
+            found: 16
+        })
+    );
+}
+
+#[test]
+fn as_u64() {
+    let dtb = reader(QEMU_VIRT_1HART);
+    let chosen = dtb.find_node("/chosen").unwrap();
+    let seed = property(&chosen, "rng-seed");
+
+    assert!(seed.as_u64().is_err());
+    assert_eq!(
+        property(&dtb.cpus_node(), "timebase-frequency").as_u64(),
+        Err(PropertyError::InvalidLength {
+            expected: 8,
+            found: 4
+        })
+    );
+
+    let dtb = reader(TRANSLATION);
+    assert_eq!(
+        property(&dtb.root_node(), "meos,u64").as_u64(),
+        Ok(0x1_0000_0002)
+    );
+}
+
+#[test]
+fn as_cells() {
+    let dtb = reader(HIFIVE_UNLEASHED);
+    let cache = dtb.find_node("/soc/cache-controller").unwrap();
+
+    let interrupts = property(&cache, "interrupts").as_cells().unwrap();
+    assert_eq!(interrupts.len(), 3);
+    assert_eq!(interrupts.iter().collect::<Vec<_>>(), [1, 2, 3]);
+    assert_eq!(interrupts.into_iter().sum::<u32>(), 6);
+
+    let eth = dtb.find_node("ethernet0").unwrap();
+    assert_eq!(
+        property(&eth, "local-mac-address").as_cells(),
+        Err(PropertyError::PartialEntry {
+            entry_size: 4,
+            found: 6
+        })
+    );
+}
+
+#[test]
+fn as_cells_chunked() {
+    let dtb = reader(HIFIVE_UNLEASHED);
+    let clint = dtb.find_node("/soc/clint").unwrap();
+    let interrupts = property(&clint, "interrupts-extended");
+
+    let pairs: Vec<Vec<u32>> = interrupts
+        .as_cells_chunked(2)
+        .unwrap()
+        .map(|c| c.iter().collect())
+        .collect();
+    assert_eq!(pairs.len(), 10);
+    assert_eq!(pairs[0][1], 3);
+    assert_eq!(pairs[1][1], 7);
+
+    assert!(matches!(
+        interrupts.as_cells_chunked(3),
+        Err(PropertyError::PartialEntry {
+            entry_size: 12,
+            found: 80
+        })
+    ));
+    assert!(interrupts.as_cells_chunked(0).is_err());
+}
+
+#[test]
+fn is_present() {
+    let dtb = reader(QEMU_VIRT_1HART);
+    let plic = dtb.find_node("/soc/plic").unwrap();
+
+    assert_eq!(plic.is_present("interrupt-controller"), Ok(true));
+    assert_eq!(plic.is_present("dma-coherent"), Ok(false));
+    assert_eq!(
+        plic.is_present("compatible"),
+        Err(PropertyError::InvalidLength {
+            expected: 0,
+            found: 30
+        })
+    );
+}
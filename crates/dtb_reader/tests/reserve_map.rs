@@ -0,0 +1,40 @@
+mod common;
+
+use common::{HIFIVE_UNLEASHED, QEMU_VIRT_1HART, QEMU_VIRT_4HARTS, reader};
+
+#[test]
+fn empty_reserve_map() {
+    assert_eq!(reader(QEMU_VIRT_1HART).reserve_entry_iter().count(), 0);
+    // OpenSBI reserves its memory with /reserved-memory nodes, not with the reserve map
+    assert_eq!(reader(QEMU_VIRT_4HARTS).reserve_entry_iter().count(), 0);
+}
+
+#[test]
+fn reserve_entries() {
+    let entries: Vec<(u64, u64)> = reader(HIFIVE_UNLEASHED)
+        .reserve_entry_iter()
+        .map(|e| (e.address(), e.size()))
+        .collect();
+
+    assert_eq!(
+        entries,
+        [(0x8000_0000, 0x20_0000), (0xfff0_0000, 0x10_0000)]
+    );
+}
+
+#[test]
+fn pointer_constructor() {
+    let blob = common::fixture(HIFIVE_UNLEASHED);
+
+    // The blob must be aligned like the boot loader would place it
+    let mut words = vec![0u32; blob.len().div_ceil(4)];
+    for (word, chunk) in words.iter_mut().zip(blob.chunks(4)) {
+        let mut raw = [0; 4];
+        raw[..chunk.len()].copy_from_slice(chunk);
+        *word = u32::from_ne_bytes(raw);
+    }
+
+    let dtb = unsafe { dtb_reader::DtbReader::new(words.as_ptr()) }.unwrap();
+    assert_eq!(dtb.total_size(), blob.len());
+    assert_eq!(dtb.reserve_entry_iter().count(), 2);
+}
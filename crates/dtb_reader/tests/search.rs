@@ -0,0 +1,82 @@
+mod common;
+
+use common::{HIFIVE_UNLEASHED, QEMU_VIRT_1HART, reader};
+
+#[test]
+fn is_enabled() {
+    let dtb = reader(HIFIVE_UNLEASHED);
+
+    assert!(dtb.find_node("serial0").unwrap().is_enabled());
+    assert!(!dtb.find_node("serial1").unwrap().is_enabled());
+    assert!(!dtb.find_node("/cpus/cpu@0").unwrap().is_enabled());
+    // No `status`
+    assert!(dtb.find_node("/cpus/cpu@1").unwrap().is_enabled());
+    assert!(dtb.root_node().is_enabled());
+}
+
+#[test]
+fn compatible_position() {
+    let dtb = reader(HIFIVE_UNLEASHED);
+    let cpu = dtb.find_node("/cpus/cpu@1").unwrap();
+
+    assert_eq!(cpu.compatible_position("sifive,u54-mc"), Some(0));
+    assert_eq!(cpu.compatible_position("riscv"), Some(2));
+    assert_eq!(cpu.compatible_position("sifive,e51"), None);
+    assert!(cpu.is_compatible("sifive,rocket0"));
+    assert!(!dtb.find_node("/chosen").unwrap().is_compatible("riscv"));
+}
+
+#[test]
+fn find_compatible_by_priority() {
+    let dtb = reader(QEMU_VIRT_1HART);
+
+    // `/platform-bus` comes first in the tree but only lists `simple-bus` as fallback
+    let buses: Vec<_> = dtb
+        .find_compatible("simple-bus")
+        .map(|n| n.full_name())
+        .collect();
+    assert_eq!(buses, ["soc", "platform-bus@4000000"]);
+}
+
+#[test]
+fn find_compatible_in_tree_order() {
+    let dtb = reader(HIFIVE_UNLEASHED);
+
+    let cpus: Vec<_> = dtb
+        .find_compatible("sifive,rocket0")
+        .map(|n| n.full_name())
+        .collect();
+    assert_eq!(cpus, ["cpu@0", "cpu@1", "cpu@2", "cpu@3", "cpu@4"]);
+
+    let uarts: Vec<_> = dtb
+        .find_compatible("sifive,uart0")
+        .map(|n| n.path().to_string())
+        .collect();
+    assert_eq!(uarts, ["/soc/serial@10010000", "/soc/serial@10011000"]);
+
+    assert_eq!(dtb.find_compatible("ns16550a").count(), 0);
+}
+
+#[test]
+fn find_compatible_root() {
+    let dtb = reader(HIFIVE_UNLEASHED);
+    let mut nodes = dtb.find_compatible("sifive,fu540");
+
+    assert_eq!(nodes.next().unwrap().full_name(), "");
+    assert!(nodes.next().is_none());
+}
+
+#[test]
+fn find_by_device_type() {
+    let dtb = reader(HIFIVE_UNLEASHED);
+
+    assert_eq!(dtb.find_by_device_type("cpu").count(), 5);
+
+    let memory: Vec<_> = dtb
+        .find_by_device_type("memory")
+        .map(|n| n.full_name())
+        .collect();
+    assert_eq!(memory, ["memory@80000000"]);
+
+    assert_eq!(dtb.find_by_device_type("pci").count(), 0);
+}
@@ -0,0 +1,216 @@
+mod common;
+
+use common::{HIFIVE_UNLEASHED, QEMU_VIRT_1HART, QEMU_VIRT_4HARTS, reader};
+use dtb_reader::DeviceTreeNode;
+
+fn child_names<'a>(node: &DeviceTreeNode<'a>) -> Vec<&'a str> {
+    node.children().map(|c| c.full_name()).collect()
+}
+
+#[test]
+fn root_children() {
+    let dtb = reader(QEMU_VIRT_1HART);
+
+    assert_eq!(dtb.root_node().full_name(), "");
+    assert_eq!(
+        child_names(&dtb.root_node()),
+        [
+            "poweroff",
+            "reboot",
+            "platform-bus@4000000",
+            "memory@80000000",
+            "cpus",
+            "pmu",
+            "fw-cfg@10100000",
+            "flash@20000000",
+            "aliases",
+            "chosen",
+            "soc",
+        ]
+    );
+}
+
+#[test]
+fn root_properties() {
+    let root = reader(QEMU_VIRT_1HART).root_node();
+
+    let names: Vec<&str> = root.properties().map(|p| p.name()).collect();
+    assert_eq!(
+        names,
+        ["#address-cells", "#size-cells", "compatible", "model"]
+    );
+
+    assert_eq!(
+        root.get_property("#address-cells").unwrap().raw_value(),
+        [0, 0, 0, 2]
+    );
+    assert_eq!(
+        root.get_property("model").unwrap().value_str(),
+        Some("riscv-virtio,qemu")
+    );
+    assert!(root.get_property("missing").is_none());
+}
+
+#[test]
+fn node_name_and_address() {
+    let memory = reader(QEMU_VIRT_1HART)
+        .root_node()
+        .get_child("memory")
+        .unwrap();
+
+    assert_eq!(memory.full_name(), "memory@80000000");
+    assert_eq!(memory.name(), "memory");
+    assert_eq!(memory.address(), Some("80000000"));
+    assert_eq!(
+        memory.get_property("device_type").unwrap().value_str(),
+        Some("memory")
+    );
+
+    let chosen = reader(QEMU_VIRT_1HART)
+        .root_node()
+        .get_child("chosen")
+        .unwrap();
+    assert_eq!(chosen.address(), None);
+}
+
+#[test]
+fn property_values() {
+    let dtb = reader(QEMU_VIRT_1HART);
+    let soc = dtb.root_node().get_child("soc").unwrap();
+
+    let serial = soc.get_child("serial").unwrap();
+    assert_eq!(
+        serial.get_property("reg").unwrap().raw_value(),
+        [0, 0, 0, 0, 0x10, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x01, 0]
+    );
+
+    // String lists are NUL separated, they are not a single string
+    let test = soc.get_child("test").unwrap();
+    let compatible = test.get_property("compatible").unwrap();
+    assert_eq!(
+        compatible.raw_value(),
+        b"sifive,test1\0sifive,test0\0syscon\0"
+    );
+    assert_eq!(compatible.value_str(), None);
+
+    // Empty properties are flags
+    let plic = soc.get_child("plic").unwrap();
+    let interrupt_controller = plic.get_property("interrupt-controller").unwrap();
+    assert!(interrupt_controller.raw_value().is_empty());
+
+    let hifive = reader(HIFIVE_UNLEASHED);
+    let ethernet = hifive.find_node("ethernet0").unwrap();
+    assert_eq!(
+        ethernet
+            .get_property("local-mac-address")
+            .unwrap()
+            .raw_value(),
+        [0x70, 0xb3, 0xd5, 0x92, 0xf0, 0x2c]
+    );
+}
+
+#[test]
+fn cpus() {
+    let hart_count = |name| {
+        reader(name)
+            .cpus_node()
+            .children()
+            .filter(|c| c.name() == "cpu")
+            .count()
+    };
+
+    assert_eq!(hart_count(QEMU_VIRT_1HART), 1);
+    assert_eq!(hart_count(QEMU_VIRT_4HARTS), 4);
+    assert_eq!(hart_count(HIFIVE_UNLEASHED), 5);
+
+    let cpus = reader(QEMU_VIRT_4HARTS).cpus_node();
+    assert_eq!(
+        child_names(&cpus),
+        ["cpu@0", "cpu@1", "cpu@2", "cpu@3", "cpu-map"]
+    );
+
+    let cpu = cpus.children().next().unwrap();
+    assert_eq!(child_names(&cpu), ["interrupt-controller"]);
+    assert_eq!(
+        cpu.get_property("mmu-type").unwrap().value_str(),
+        Some("riscv,sv57")
+    );
+}
+
+#[test]
+fn aliases() {
+    let qemu = reader(QEMU_VIRT_1HART);
+    assert_eq!(qemu.resolve_alias("serial0"), Some("/soc/serial@10000000"));
+    assert_eq!(qemu.resolve_alias("serial1"), None);
+
+    let hifive = reader(HIFIVE_UNLEASHED);
+    assert_eq!(
+        hifive.resolve_alias("serial0"),
+        Some("/soc/serial@10010000")
+    );
+    assert_eq!(
+        hifive.resolve_alias("serial1"),
+        Some("/soc/serial@10011000")
+    );
+    assert_eq!(
+        hifive.resolve_alias("ethernet0"),
+        Some("/soc/ethernet@10090000")
+    );
+}
+
+#[test]
+fn find_node() {
+    let dtb = reader(QEMU_VIRT_4HARTS);
+
+    assert_eq!(dtb.find_node("/").unwrap().full_name(), "");
+    assert_eq!(dtb.find_node("/cpus").unwrap().full_name(), "cpus");
+    assert_eq!(
+        dtb.find_node("/cpus/cpu@2")
+            .unwrap()
+            .get_property("reg")
+            .unwrap()
+            .raw_value(),
+        [0, 0, 0, 2]
+    );
+    assert_eq!(
+        dtb.find_node("/soc/serial@10000000").unwrap().full_name(),
+        "serial@10000000"
+    );
+    assert_eq!(
+        dtb.find_node("/cpus/cpu-map/cluster0/core3")
+            .unwrap()
+            .full_name(),
+        "core3"
+    );
+
+    // Without a unit address, the first node with the name
+    assert_eq!(
+        dtb.find_node("/soc/virtio_mmio").unwrap().full_name(),
+        "virtio_mmio@10008000"
+    );
+
+    assert!(dtb.find_node("/soc/serial@10000001").is_none());
+    assert!(dtb.find_node("/missing").is_none());
+    assert!(dtb.find_node("missing-alias").is_none());
+}
+
+#[test]
+fn find_node_through_alias() {
+    let dtb = reader(HIFIVE_UNLEASHED);
+
+    let stdout_path = dtb
+        .find_node("/chosen")
+        .unwrap()
+        .get_property("stdout-path")
+        .unwrap()
+        .value_str()
+        .unwrap();
+    assert_eq!(stdout_path, "serial0");
+
+    let uart = dtb.find_node(stdout_path).unwrap();
+    assert_eq!(uart.full_name(), "serial@10010000");
+    assert_eq!(
+        uart.get_property("status").unwrap().value_str(),
+        Some("okay")
+    );
+}
@@ -49,3 +49,14 @@ switch_context: # (a0 = prev_sp, a1 = next_sp)
     # Cleanup stack pointer
     addi sp, sp, 13 * 8
     ret
+
+# Entry point of new threads, `switch_context` returns here with the
+# thread's argument in s0 (see `ProcessManager::new_process`)
+
+    .global thread_entry
+    .extern thread_start
+    .type thread_entry, @function
+
+thread_entry:
+    mv a0, s0
+    tail thread_start
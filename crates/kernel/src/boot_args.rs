@@ -0,0 +1,54 @@
+use dtb_reader::DtbReader;
+
+/// Kernel command line, the `bootargs` property of `/chosen`.
+///
+/// Options are separated by spaces, either flags like `quiet` or `key=value` pairs. A key can be
+/// given more than once.
+#[derive(Debug, Clone, Copy)]
+pub struct BootArgs<'a>(&'a str);
+
+impl<'a> BootArgs<'a> {
+    /// Empty when the DTB has no `bootargs`
+    pub fn from_dtb(dtb: &DtbReader<'a>) -> BootArgs<'a> {
+        let bootargs = dtb
+            .find_node("/chosen")
+            .and_then(|chosen| chosen.get_property("bootargs"))
+            .and_then(|bootargs| bootargs.value_str());
+
+        BootArgs(bootargs.unwrap_or_default())
+    }
+
+    /// Every option with its value, `None` for flags
+    pub fn options(&self) -> impl Iterator<Item = (&'a str, Option<&'a str>)> + use<'a> {
+        self.0
+            .split_ascii_whitespace()
+            .map(|option| match option.split_once('=') {
+                Some((key, value)) => (key, Some(value)),
+                None => (option, None),
+            })
+    }
+
+    /// Whether the flag `name` is given, without value
+    pub fn has_flag(&self, name: &str) -> bool {
+        self.options()
+            .any(|(key, value)| key == name && value.is_none())
+    }
+
+    /// Values of every `key=value` option with this key, in order
+    pub fn values<'k>(&self, key: &'k str) -> impl Iterator<Item = &'a str> + use<'a, 'k> {
+        self.options()
+            .filter(move |(k, _)| *k == key)
+            .filter_map(|(_, value)| value)
+    }
+}
+
+/// Parses an address given in hexadecimal with a `0x` prefix, or in decimal
+pub fn parse_address(value: &str) -> Option<usize> {
+    match value
+        .strip_prefix("0x")
+        .or_else(|| value.strip_prefix("0X"))
+    {
+        Some(hex) => usize::from_str_radix(hex, 16).ok(),
+        None => value.parse().ok(),
+    }
+}
@@ -1,9 +1,242 @@
+use core::fmt::{self, Display};
+
+use log::{error, warn};
+use riscv::interrupt::supervisor as interrupt;
+use riscv::register::{
+    sie, sip, sstatus,
+    stvec::{self, Stvec, TrapMode},
+};
+use spin::Mutex;
+
+use crate::trap_entry;
+
+const INTERRUPT_BIT: usize = 1 << (usize::BITS - 1);
+const MAX_INTERRUPT_CODE: usize = 16;
+
+const REGISTER_NAMES: [&str; 32] = [
+    "zero", "ra", "sp", "gp", "tp", "t0", "t1", "t2", "s0", "s1", "a0", "a1", "a2", "a3", "a4",
+    "a5", "a6", "a7", "s2", "s3", "s4", "s5", "s6", "s7", "s8", "s9", "s10", "s11", "t3", "t4",
+    "t5", "t6",
+];
+
+pub type InterruptHandler = fn(&mut TrapFrame);
+
+// NOTE: The lock must only be taken with interrupts disabled, otherwise an interrupt could try
+// to dispatch while the interrupted code holds it.
+static INTERRUPT_HANDLERS: Mutex<[Option<InterruptHandler>; MAX_INTERRUPT_CODE]> =
+    Mutex::new([None; MAX_INTERRUPT_CODE]);
+
+/// Registers saved by `trap_entry` (see `asm/riscv64/trap.s`)
+#[repr(C)]
+#[derive(Debug)]
+pub struct TrapFrame {
+    /// General purpose registers `x0` to `x31`, indexed by register number
+    pub regs: [usize; 32],
+    pub sepc: usize,
+    pub scause: usize,
+    pub stval: usize,
+    pub sstatus: usize,
+}
+
+impl Display for TrapFrame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "sepc={:#018x} scause={:#018x} stval={:#018x} sstatus={:#018x}",
+            self.sepc, self.scause, self.stval, self.sstatus
+        )?;
+
+        for (i, chunk) in self.regs.chunks(4).enumerate() {
+            for (j, reg) in chunk.iter().enumerate() {
+                write!(f, "{:>4}={:#018x} ", REGISTER_NAMES[i * 4 + j], reg)?;
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Interrupt {
+    SupervisorSoftware,
+    SupervisorTimer,
+    SupervisorExternal,
+    Unknown(usize),
+}
+
+impl Interrupt {
+    pub fn code(&self) -> usize {
+        match self {
+            Interrupt::SupervisorSoftware => 1,
+            Interrupt::SupervisorTimer => 5,
+            Interrupt::SupervisorExternal => 9,
+            Interrupt::Unknown(code) => *code,
+        }
+    }
+}
+
+impl From<usize> for Interrupt {
+    fn from(code: usize) -> Self {
+        match code {
+            1 => Self::SupervisorSoftware,
+            5 => Self::SupervisorTimer,
+            9 => Self::SupervisorExternal,
+            _ => Self::Unknown(code),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Exception {
+    InstructionMisaligned,
+    InstructionFault,
+    IllegalInstruction,
+    Breakpoint,
+    LoadMisaligned,
+    LoadFault,
+    StoreMisaligned,
+    StoreFault,
+    UserEnvCall,
+    SupervisorEnvCall,
+    InstructionPageFault,
+    LoadPageFault,
+    StorePageFault,
+    SoftwareCheck,
+    HardwareError,
+    Unknown(usize),
+}
+
+impl From<usize> for Exception {
+    fn from(code: usize) -> Self {
+        match code {
+            0 => Self::InstructionMisaligned,
+            1 => Self::InstructionFault,
+            2 => Self::IllegalInstruction,
+            3 => Self::Breakpoint,
+            4 => Self::LoadMisaligned,
+            5 => Self::LoadFault,
+            6 => Self::StoreMisaligned,
+            7 => Self::StoreFault,
+            8 => Self::UserEnvCall,
+            9 => Self::SupervisorEnvCall,
+            12 => Self::InstructionPageFault,
+            13 => Self::LoadPageFault,
+            15 => Self::StorePageFault,
+            18 => Self::SoftwareCheck,
+            19 => Self::HardwareError,
+            _ => Self::Unknown(code),
+        }
+    }
+}
+
+/// Decoded `scause` value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Trap {
+    Interrupt(Interrupt),
+    Exception(Exception),
+}
+
+impl From<usize> for Trap {
+    fn from(scause: usize) -> Self {
+        let code = scause & !INTERRUPT_BIT;
+        if scause & INTERRUPT_BIT != 0 {
+            Self::Interrupt(Interrupt::from(code))
+        } else {
+            Self::Exception(Exception::from(code))
+        }
+    }
+}
+
+/// Installs the trap vector, exceptions are reported from then on
+pub fn install_trap_vector() {
+    unsafe {
+        stvec::write(Stvec::new(
+            trap_entry as *const () as usize,
+            TrapMode::Direct,
+        ));
+    }
+}
+
+/// Enables supervisor interrupts, `install_trap_vector` must have been called
 pub fn setup() {
+    // Inter-processor interrupts only wake the hart for now
+    register_handler(Interrupt::SupervisorSoftware, clear_software_interrupt);
+
+    unsafe { sstatus::set_sie() };
+}
+
+/// Registers the handler of an interrupt source and enables it.
+/// Registering a new handler for the same interrupt replaces the previous one.
+pub fn register_handler(interrupt: Interrupt, handler: InterruptHandler) {
+    let code = interrupt.code();
+    assert!(code < MAX_INTERRUPT_CODE, "invalid interrupt code {code}");
+
+    interrupt::free(|| INTERRUPT_HANDLERS.lock()[code] = Some(handler));
+
     unsafe {
-        // riscv::register::mstatus::set_mie(); // Enable Machine Interrupt
-        // riscv::register::mstatus::set_sie(); // Enable Supervisor Interrupt
-        //
-        // riscv::register::mie::set_mtimer(); // Enable Machine Timer Interrupt
-        // riscv::register::mie::set_stimer(); // Enable Supervisor Timer Interrupt
+        match interrupt {
+            Interrupt::SupervisorSoftware => sie::set_ssoft(),
+            Interrupt::SupervisorTimer => sie::set_stimer(),
+            Interrupt::SupervisorExternal => sie::set_sext(),
+            Interrupt::Unknown(_) => {}
+        }
     }
 }
+
+fn clear_software_interrupt(_frame: &mut TrapFrame) {
+    // The sender sets `sip.SSIP`, it stays pending until it is cleared
+    unsafe { sip::clear_ssoft() };
+}
+
+/// Called by `trap_entry` with the saved state of the interrupted context
+#[unsafe(no_mangle)]
+extern "C" fn trap_handler(frame: &mut TrapFrame) {
+    match Trap::from(frame.scause) {
+        Trap::Interrupt(interrupt) => handle_interrupt(interrupt, frame),
+        Trap::Exception(exception) => handle_exception(exception, frame),
+    }
+}
+
+fn handle_interrupt(interrupt: Interrupt, frame: &mut TrapFrame) {
+    // Copy the handler out so the lock is not held while it runs
+    let handler = INTERRUPT_HANDLERS
+        .lock()
+        .get(interrupt.code())
+        .copied()
+        .flatten();
+
+    match handler {
+        Some(handler) => handler(frame),
+        None => {
+            error!("Unhandled interrupt {interrupt:?}, disabling it");
+            unsafe {
+                match interrupt {
+                    Interrupt::SupervisorSoftware => sie::clear_ssoft(),
+                    Interrupt::SupervisorTimer => sie::clear_stimer(),
+                    Interrupt::SupervisorExternal => sie::clear_sext(),
+                    Interrupt::Unknown(_) => {}
+                }
+            }
+        }
+    }
+}
+
+fn handle_exception(exception: Exception, frame: &mut TrapFrame) {
+    match exception {
+        Exception::Breakpoint => {
+            warn!("Breakpoint @ {:#x}", frame.sepc);
+            frame.sepc += instruction_length(frame.sepc);
+        }
+        _ => {
+            error!("Unhandled exception {exception:?}\n{frame}");
+            panic!("Unhandled exception {exception:?} @ {:#x}", frame.sepc);
+        }
+    }
+}
+
+/// Length in bytes of the instruction at `address`, compressed instructions are 2 bytes long
+fn instruction_length(address: usize) -> usize {
+    let low_bits = unsafe { core::ptr::read_volatile(address as *const u16) };
+    if low_bits & 0b11 == 0b11 { 4 } else { 2 }
+}
@@ -3,36 +3,58 @@
 
 extern crate alloc;
 
+mod boot_args;
 mod interrupts;
+mod memory;
+mod overlays;
 mod process;
+mod scheduler;
 mod time;
 
+use alloc::vec::Vec;
+use allocator::FramePool;
 use core::arch::{asm, global_asm};
 use core::panic::PanicInfo;
-use drivers::{DriverManager, UartDriver};
+use drivers::{DriverManager, RtcDriver, UartDriver};
 use dtb_reader::DtbReader;
-use log::{add_logger, error, info};
+use log::{LogLevel, LogWriter, add_logger, debug, error, info, warn};
 
-use allocator::{BumpAllocator, GlobalAllocator};
+use crate::boot_args::BootArgs;
+use crate::memory::{FRAME_ALLOCATOR, GLOBAL_ALLOCATOR, KernelFrames};
+use crate::overlays::BootOverlays;
+use crate::time::{Duration, Instant, SystemTime};
 
-use crate::process::ProcessManager;
-use crate::time::Time;
-
-#[global_allocator]
-static GLOBAL_ALLOCATOR: GlobalAllocator<BumpAllocator> = GlobalAllocator::new();
+const WORKERS_TIMEOUT: Duration = Duration::from_secs(5);
+const STATS_PERIOD: Duration = Duration::from_secs(5);
 
 global_asm!(include_str!("asm/riscv64/entry.s"));
 unsafe extern "C" {
+    static _KERNEL_START: u8;
     static mut _KERNEL_END: usize;
 }
 
 global_asm!(include_str!("asm/riscv64/switch.s"));
 unsafe extern "C" {
     pub fn switch_context(prev_context_sp: *mut usize, next_context_sp: *const usize);
+    pub fn thread_entry();
 }
 
+global_asm!(include_str!("asm/riscv64/trap.s"));
+unsafe extern "C" {
+    pub fn trap_entry();
+}
+
+/// Kernel entry point, called by `_start` in `entry.s`
+///
+/// # Safety
+///
+/// `dtb_ptr` must point to a valid Device Tree Blob that is never overwritten, like the overlays
+/// given with `overlay=<address>` boot options.
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn main(hw_thread_id: usize, dtb_ptr: *const u32) -> ! {
+    // Before anything can trap
+    interrupts::install_trap_vector();
+
     // Single threaded for now
     if hw_thread_id != 0 {
         loop {
@@ -41,9 +63,16 @@ pub unsafe extern "C" fn main(hw_thread_id: usize, dtb_ptr: *const u32) -> ! {
     }
 
     let dtb = unsafe { DtbReader::new(dtb_ptr).expect("failed to parse DTB") };
+    let boot_args = BootArgs::from_dtb(&dtb);
+    let mut overlays = unsafe { BootOverlays::from_boot_args(&boot_args) };
+
+    // NOTE: Memory nodes added by overlays are ignored, the heap is needed to apply them
+    let usable_memory = memory::init(&dtb, overlays.blobs());
+
+    let dtb = overlays.apply(dtb);
     let dtb_root = dtb.root_node();
 
-    init_allocator(dtb_root);
+    time::init(&dtb, hw_thread_id);
 
     let mut driver_manager = DriverManager::default();
     driver_manager.load_drivers(&dtb_root);
@@ -59,59 +88,128 @@ pub unsafe extern "C" fn main(hw_thread_id: usize, dtb_ptr: *const u32) -> ! {
         .get_by_path::<dyn UartDriver>(stdout_path)
         .unwrap();
     add_logger(stdout_uart);
+    driver_manager.log();
+
+    let rtc = driver_manager.get_first::<dyn RtcDriver>();
+    if let Some(rtc) = &rtc {
+        time::sync_with_rtc(&mut *rtc.lock());
+    }
+    log::set_timestamp(|w| write!(w, "{:.3}", SystemTime::now()));
 
+    overlays.log();
+
+    if boot_args.has_flag("dump-dtb") {
+        dtb.dump(&mut LogWriter::new(LogLevel::Info))
+            .expect("failed to dump DTB");
+    }
     info!("Stdout Path: {stdout_path}");
+    info!(
+        "Usable memory: {} KB in {usable_memory:?}",
+        usable_memory.total_size() / 1024
+    );
+
+    if let (Ok(version), Ok(implementation)) =
+        (sbi::base::get_spec_version(), sbi::base::get_impl_id())
+    {
+        info!(
+            "SBI v{}.{} ({implementation:?})",
+            version.major, version.minor
+        );
+    }
 
-    info!("Initializing process manager...");
-    let process_manager = ProcessManager::default();
-    info!("Process manager initialized.");
+    info!(
+        "Timebase: {} Hz, timer: {}",
+        time::ticks_per_second(),
+        if time::uses_stimecmp() {
+            "stimecmp"
+        } else {
+            "SBI"
+        }
+    );
+    match rtc {
+        Some(_) => {
+            let now = SystemTime::now();
+            info!("Wall clock: {now} (UNIX {})", now.unix_time().as_secs());
+        }
+        None => warn!("No RTC, the wall clock counts from the UNIX epoch"),
+    }
+    for cpu in dtb.cpus() {
+        info!(
+            "Hart {} ({}): rv{} {}, MMU {:?}",
+            cpu.hart_id().unwrap_or_default(),
+            cpu.status(),
+            cpu.xlen().unwrap_or_default(),
+            cpu.extensions(),
+            cpu.mmu_type()
+        );
+    }
 
     interrupts::setup();
+    scheduler::start_preemption();
 
-    loop {
-        delay();
-        let available_ram = GLOBAL_ALLOCATOR.get_available() / 1024;
+    let marker = GLOBAL_ALLOCATOR.marker();
 
-        info!("RAM available: {available_ram} KB");
-        info!("Cycle: {}", riscv::register::cycle::read64());
-        info!("Time: {}", Time::get().as_millis());
-    }
-}
+    let start = Instant::now();
+    // Expires if the workers are not done in time, nothing to do but report it
+    let watchdog = time::set_timeout(WORKERS_TIMEOUT, || {});
 
-fn init_allocator(dtb_root: dtb_reader::DeviceTreeNode) {
-    for node in dtb_root.children() {
-        if let Some(device_type) = node.get_property("device_type")
-            && device_type.value_str().unwrap() == "memory"
-        {
-            let prop = node.get_property("reg").unwrap();
-            let reg = prop.raw_value();
-            let address = usize::from_be_bytes(reg[0..8].try_into().unwrap());
-            let size = usize::from_be_bytes(reg[8..16].try_into().unwrap());
+    let workers: Vec<usize> = (0..3)
+        .map(|id| scheduler::spawn(move || worker(id)).expect("failed to spawn worker"))
+        .collect();
 
-            let kernel_end = unsafe { _KERNEL_END };
+    for pid in workers {
+        scheduler::join(pid).unwrap();
+        info!("Process {pid} joined");
+    }
 
-            let end = address + size;
+    info!("Workers done in {} ms", start.elapsed().as_millis());
+    if !time::cancel(watchdog) {
+        warn!("Workers took more than {WORKERS_TIMEOUT:?}");
+    }
 
-            let start = if kernel_end > address && kernel_end < end {
-                kernel_end
-            } else {
-                address
-            };
+    // Everything the workers allocated should be freed once they are reaped
+    if let Some(marker) = marker {
+        memory::log_outstanding(marker);
+    }
+
+    loop {
+        scheduler::sleep(STATS_PERIOD);
+        let available_ram = GLOBAL_ALLOCATOR.get_available() / 1024;
+        let available_frames = KernelFrames::free_frames();
 
-            GLOBAL_ALLOCATOR.init(start, end);
-            break;
+        info!("RAM available: {available_ram} KB");
+        info!("Frames available: {available_frames}");
+        memory::log_stats();
+        for stats in GLOBAL_ALLOCATOR.cache_stats() {
+            debug!(
+                "Slab cache {:>4} B: {} in use, {} free, {} slabs, {} B wasted",
+                stats.object_size,
+                stats.objects_in_use,
+                stats.objects_free,
+                stats.slabs,
+                stats.waste
+            );
         }
+        info!("Cycle: {}", riscv::register::cycle::read64());
+        info!("Uptime: {} ms", time::uptime().as_millis());
     }
 }
 
-fn delay() {
-    for _ in 0..i32::MAX {
-        unsafe { asm!("nop") };
+fn worker(id: usize) {
+    for step in 0..5 {
+        info!("Worker {id}: step {step}");
+        for _ in 0..1_000_000 {
+            unsafe { asm!("nop") };
+        }
     }
 }
 
 #[panic_handler]
 fn panic(info: &PanicInfo) -> ! {
+    // The panic may come from inside the allocator, logging needs the heap
+    unsafe { GLOBAL_ALLOCATOR.force_unlock() };
+    unsafe { FRAME_ALLOCATOR.force_unlock() };
+
     if let Some(location) = info.location() {
         error!(
             "KERNEL PANIC @ line {}, col {} in {}: \nDetails:\n\t{}",
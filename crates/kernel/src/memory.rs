@@ -0,0 +1,135 @@
+use allocator::{
+    AllocStats, FrameAllocator, FramePool, GlobalAllocator, PhysAddr, RegionSet, SlabAllocator,
+};
+use dtb_reader::DtbReader;
+use log::{debug, info};
+use riscv::interrupt::supervisor as interrupt;
+use spin::Mutex;
+
+use crate::{_KERNEL_END, _KERNEL_START};
+
+#[cfg(not(feature = "debug-heap"))]
+type HeapAllocator = SlabAllocator<KernelFrames>;
+#[cfg(feature = "debug-heap")]
+type HeapAllocator = allocator::DebugAllocator<SlabAllocator<KernelFrames>>;
+
+#[global_allocator]
+pub static GLOBAL_ALLOCATOR: GlobalAllocator<HeapAllocator> = GlobalAllocator::new();
+
+/// Physical frames of the usable memory, the heap takes its pages from them
+// NOTE: The lock must only be taken with interrupts disabled, and nothing may be allocated on the
+// heap while it is held: the heap takes it when it grows.
+pub static FRAME_ALLOCATOR: Mutex<FrameAllocator> = Mutex::new(FrameAllocator::new());
+
+/// Page level of the heap, backed by `FRAME_ALLOCATOR`
+pub struct KernelFrames;
+
+impl FramePool for KernelFrames {
+    unsafe fn add_region(start: PhysAddr, end: PhysAddr) {
+        interrupt::free(|| unsafe { FRAME_ALLOCATOR.lock().add_region(start, end) });
+    }
+
+    fn allocate_frames(count: usize) -> Option<PhysAddr> {
+        interrupt::free(|| FRAME_ALLOCATOR.lock().allocate_frames(count))
+    }
+
+    fn deallocate_frames(address: PhysAddr, count: usize) {
+        interrupt::free(|| FRAME_ALLOCATOR.lock().deallocate_frames(address, count));
+    }
+
+    fn free_frames() -> usize {
+        interrupt::free(|| FRAME_ALLOCATOR.lock().free_frames())
+    }
+}
+
+/// Initializes the heap and the frame allocator with every usable memory range of the DTB.
+/// Returns the usable ranges.
+///
+/// Usable memory is the union of every `reg` entry of the memory nodes minus the kernel image,
+/// the DTB itself, the other `blobs` given by the bootloader, the memory reservation block and
+/// the `/reserved-memory` children.
+pub fn init<'a>(dtb: &DtbReader<'a>, blobs: impl IntoIterator<Item = &'a [u8]>) -> RegionSet {
+    let root = dtb.root_node();
+
+    let mut usable = RegionSet::new();
+
+    for node in dtb.find_by_device_type("memory") {
+        for (address, size) in node.reg() {
+            usable.add(address as usize, address.saturating_add(size) as usize);
+        }
+    }
+
+    let kernel_start = &raw const _KERNEL_START as usize;
+    let kernel_end = &raw const _KERNEL_END as usize;
+    usable.remove(kernel_start, kernel_end);
+
+    for blob in core::iter::once(dtb.as_bytes()).chain(blobs) {
+        let range = blob.as_ptr_range();
+        usable.remove(range.start as usize, range.end as usize);
+    }
+
+    for entry in dtb.reserve_entry_iter() {
+        let address = entry.address() as usize;
+        usable.remove(address, address.saturating_add(entry.size() as usize));
+    }
+
+    if let Some(reserved) = root.get_child("reserved-memory") {
+        for child in reserved.children() {
+            for (address, size) in child.reg() {
+                usable.remove(address as usize, address.saturating_add(size) as usize);
+            }
+        }
+    }
+
+    // Every usable frame goes to `FRAME_ALLOCATOR`, through the heap
+    GLOBAL_ALLOCATOR.init(&usable);
+    GLOBAL_ALLOCATOR.enable_stats();
+    if cfg!(feature = "track-heap") {
+        GLOBAL_ALLOCATOR.enable_tracking();
+    }
+
+    usable
+}
+
+/// Logs every allocation made since `marker` that is still live
+pub fn log_outstanding(marker: u64) {
+    let mut count = 0;
+    GLOBAL_ALLOCATOR.for_each_outstanding(marker, |allocation| {
+        count += 1;
+        debug!(
+            "Outstanding #{}: {:#x} ({} B, align {})",
+            allocation.id,
+            allocation.address,
+            allocation.layout.size(),
+            allocation.layout.align()
+        );
+    });
+
+    let untracked = GLOBAL_ALLOCATOR.untracked_allocations();
+    info!("{count} allocations outstanding since marker {marker}, {untracked} untracked");
+}
+
+pub fn log_stats() {
+    let Some(stats) = GLOBAL_ALLOCATOR.stats() else {
+        return;
+    };
+
+    info!(
+        "Heap: {} allocs, {} frees, {} reallocs, {} failures, {} B in use, {} B peak",
+        stats.allocations,
+        stats.frees,
+        stats.reallocations,
+        stats.failures,
+        stats.bytes_in_use,
+        stats.peak_bytes
+    );
+    for (bucket, count) in stats.size_histogram.iter().enumerate() {
+        if *count == 0 {
+            continue;
+        }
+        match AllocStats::bucket_limit(bucket) {
+            Some(limit) => debug!("Allocations <= {limit} B: {count}"),
+            None => debug!("Allocations bigger: {count}"),
+        }
+    }
+}
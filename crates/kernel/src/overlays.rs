@@ -0,0 +1,149 @@
+use alloc::vec::Vec;
+use core::fmt::{self, Display};
+
+use dtb_reader::{DtbInitError, DtbReader, Overlay, OverlayError, apply_overlay};
+use log::{error, info, warn};
+
+use crate::boot_args::{BootArgs, parse_address};
+
+const MAX_OVERLAYS: usize = 8;
+
+#[derive(Debug)]
+pub enum LoadError {
+    InvalidAddress,
+    Parse(DtbInitError),
+    Apply(OverlayError),
+    /// The tree with the overlay applied cannot be parsed
+    Merge(DtbInitError),
+}
+
+impl Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadError::InvalidAddress => write!(f, "invalid address"),
+            LoadError::Parse(error) => write!(f, "invalid blob: {error:?}"),
+            LoadError::Apply(error) => write!(f, "{error}"),
+            LoadError::Merge(error) => write!(f, "invalid merged tree: {error:?}"),
+        }
+    }
+}
+
+/// Overlay given with an `overlay=<address>` boot option
+struct BootOverlay {
+    /// Value of the boot option
+    address: &'static str,
+    overlay: Result<Overlay<'static>, LoadError>,
+}
+
+/// Device tree overlays loaded in memory by the bootloader or QEMU (`-device loader`).
+///
+/// They are found before the heap is ready, so their blobs can be kept out of the usable memory,
+/// and applied once it is.
+pub struct BootOverlays {
+    overlays: [Option<BootOverlay>; MAX_OVERLAYS],
+    // Options past `MAX_OVERLAYS`
+    ignored: usize,
+}
+
+impl BootOverlays {
+    /// Finds the overlays of every `overlay=<address>` option
+    ///
+    /// # Safety
+    ///
+    /// Every address given must point to a blob that is never overwritten.
+    pub unsafe fn from_boot_args(args: &BootArgs<'static>) -> BootOverlays {
+        let mut overlays = BootOverlays {
+            overlays: [const { None }; MAX_OVERLAYS],
+            ignored: 0,
+        };
+
+        for (i, address) in args.values("overlay").enumerate() {
+            let Some(slot) = overlays.overlays.get_mut(i) else {
+                overlays.ignored += 1;
+                continue;
+            };
+
+            let overlay = match parse_address(address) {
+                Some(ptr) => unsafe { Overlay::new(ptr as *const u32).map_err(LoadError::Parse) },
+                None => Err(LoadError::InvalidAddress),
+            };
+            *slot = Some(BootOverlay { address, overlay });
+        }
+
+        overlays
+    }
+
+    /// Blobs of the valid overlays
+    pub fn blobs(&self) -> impl Iterator<Item = &'static [u8]> + use<'_> {
+        self.iter()
+            .filter_map(|o| o.overlay.as_ref().ok())
+            .map(|overlay| overlay.as_bytes())
+    }
+
+    /// Applies the overlays in order on `dtb` and returns the merged tree. An overlay that cannot
+    /// be applied, or whose result cannot be parsed, is skipped and the last valid tree is kept.
+    /// Errors are kept for `log`.
+    pub fn apply(&mut self, dtb: DtbReader<'static>) -> DtbReader<'static> {
+        // Last merged tree, only kept once it parses
+        let mut merged: Option<Vec<u8>> = None;
+
+        for boot_overlay in self.overlays.iter_mut().flatten() {
+            let Ok(overlay) = &boot_overlay.overlay else {
+                continue;
+            };
+
+            let result = match &merged {
+                Some(blob) => DtbReader::from_bytes(blob)
+                    .map_err(LoadError::Merge)
+                    .and_then(|base| apply_overlay(&base, overlay).map_err(LoadError::Apply)),
+                None => apply_overlay(&dtb, overlay).map_err(LoadError::Apply),
+            };
+
+            match result.and_then(check_merged) {
+                Ok(blob) => merged = Some(blob),
+                Err(error) => boot_overlay.overlay = Err(error),
+            }
+        }
+
+        match merged {
+            // The merged tree is used until shutdown, it was checked when it was built
+            Some(blob) => DtbReader::from_bytes(blob.leak()).unwrap_or(dtb),
+            None => dtb,
+        }
+    }
+
+    /// Logs the result of `apply`, the logger is not ready when the overlays are applied
+    pub fn log(&self) {
+        for boot_overlay in self.iter() {
+            match &boot_overlay.overlay {
+                Ok(overlay) => info!(
+                    "Applied DT overlay at {} ({} B)",
+                    boot_overlay.address,
+                    overlay.as_bytes().len()
+                ),
+                Err(error) => error!("Skipped DT overlay at {}: {error}", boot_overlay.address),
+            }
+        }
+
+        if self.ignored > 0 {
+            warn!(
+                "Ignored {} DT overlays, at most {MAX_OVERLAYS} are supported",
+                self.ignored
+            );
+        }
+    }
+
+    //
+    // NON-PUBLIC INTERFACE
+    //
+
+    fn iter(&self) -> impl Iterator<Item = &BootOverlay> {
+        self.overlays.iter().flatten()
+    }
+}
+
+/// `blob` if it is a valid tree
+fn check_merged(blob: Vec<u8>) -> Result<Vec<u8>, LoadError> {
+    DtbReader::from_bytes(&blob).map_err(LoadError::Merge)?;
+    Ok(blob)
+}
@@ -1,47 +1,299 @@
-const MAX_PROCESS: usize = 8;
-const STACK_SIZE: usize = 8192;
+use core::{alloc::Layout, ptr::NonNull};
+
+use alloc::{
+    alloc::{alloc, dealloc},
+    collections::vec_deque::VecDeque,
+    vec::Vec,
+};
+
+pub const DEFAULT_STACK_SIZE: usize = 64 * 1024;
+const STACK_ALIGN: usize = 16;
+
+// Number of registers pushed by `switch_context` (ra + s0-s11)
+const CONTEXT_SIZE: usize = 13;
+
+// Bottom of every stack is filled with this pattern, it is checked on every context switch
+const GUARD_SIZE: usize = 32;
+const GUARD_PATTERN: usize = 0x57AC_4B0F_57AC_4B0F;
+
+const MIN_STACK_SIZE: usize = (GUARD_SIZE + CONTEXT_SIZE) * size_of::<usize>() + 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ProcessState {
+    Ready,
+    Running,
+    /// Waits for `ProcessManager::wake`, it is not in the run queue
+    Blocked,
+    Exited,
+}
+
+/// Heap allocated kernel stack with a guard region at its bottom
+struct Stack {
+    memory: NonNull<usize>,
+    layout: Layout,
+}
+
+impl Stack {
+    fn new(size: usize) -> Result<Stack, &'static str> {
+        if size < MIN_STACK_SIZE {
+            return Err("Stack size too small");
+        }
+
+        let layout = Layout::from_size_align(size, STACK_ALIGN)
+            .map_err(|_| "Invalid stack size")?
+            .pad_to_align();
+        let memory = NonNull::new(unsafe { alloc(layout) } as *mut usize)
+            .ok_or("Failed to allocate stack")?;
+
+        let mut stack = Stack { memory, layout };
+        stack.words_mut().fill(0);
+        stack.words_mut()[..GUARD_SIZE].fill(GUARD_PATTERN);
+
+        Ok(stack)
+    }
+
+    fn len(&self) -> usize {
+        self.layout.size() / size_of::<usize>()
+    }
+
+    fn words_mut(&mut self) -> &mut [usize] {
+        unsafe { core::slice::from_raw_parts_mut(self.memory.as_ptr(), self.len()) }
+    }
+
+    /// Returns `true` if the guard region was not overwritten
+    fn is_intact(&self) -> bool {
+        // Read only the guard, the rest of the stack may be in use by its process
+        let guard = unsafe { core::slice::from_raw_parts(self.memory.as_ptr(), GUARD_SIZE) };
+        guard.iter().all(|&w| w == GUARD_PATTERN)
+    }
+
+    fn range(&self) -> core::ops::Range<usize> {
+        let start = self.memory.as_ptr() as usize;
+        start..start + self.layout.size()
+    }
+}
+
+impl Drop for Stack {
+    fn drop(&mut self) {
+        unsafe { dealloc(self.memory.as_ptr() as *mut u8, self.layout) };
+    }
+}
+
+// The stack is only accessed by its owning process or with the process manager locked
+unsafe impl Send for Stack {}
 
 struct Process {
-    pid: usize,
+    state: ProcessState,
     stack_pointer: usize,
-    stack: [usize; STACK_SIZE],
+    stack: Stack,
 }
 
-#[derive(Default)]
+impl Process {
+    /// Creates a process that starts at `program_counter` with `argument` in `s0`
+    fn new(program_counter: usize, argument: usize, mut stack: Stack) -> Process {
+        let words = stack.words_mut();
+        let len = words.len();
+
+        // Initial frame popped by `switch_context`
+        words[len - 1] = 0; // s11
+        words[len - 2] = 0; // s10
+        words[len - 3] = 0; // s9
+        words[len - 4] = 0; // s8
+        words[len - 5] = 0; // s7
+        words[len - 6] = 0; // s6
+        words[len - 7] = 0; // s5
+        words[len - 8] = 0; // s4
+        words[len - 9] = 0; // s3
+        words[len - 10] = 0; // s2
+        words[len - 11] = 0; // s1
+        words[len - 12] = argument; // s0
+        words[len - 13] = program_counter; // ra
+
+        let stack_pointer = &words[len - CONTEXT_SIZE] as *const usize as usize;
+
+        Process {
+            state: ProcessState::Ready,
+            stack_pointer,
+            stack,
+        }
+    }
+
+    /// Panics if the process overflowed its stack
+    fn check_stack(&self, pid: usize) {
+        let range = self.stack.range();
+
+        if !self.stack.is_intact() {
+            panic!(
+                "Stack overflow in process {pid}: guard region of stack {:#x}..{:#x} was overwritten",
+                range.start, range.end
+            );
+        }
+
+        if self.state != ProcessState::Running && !range.contains(&self.stack_pointer) {
+            panic!(
+                "Stack pointer {:#x} of process {pid} is outside of its stack {:#x}..{:#x}",
+                self.stack_pointer, range.start, range.end
+            );
+        }
+    }
+}
+
+/// Kernel threads and their run queue
+///
+/// The context that is running when the manager is created (the boot context) is the idle context:
+/// it only gets scheduled when no other process is ready.
 pub struct ProcessManager {
-    procs: [Option<Process>; MAX_PROCESS],
+    // Slots are reused, a pid is the index of its slot
+    procs: Vec<Option<Process>>,
+    run_queue: VecDeque<usize>,
+    // `None` when the idle context is running
+    current: Option<usize>,
+    idle_stack_pointer: usize,
 }
 
 impl ProcessManager {
-    fn new_process(&mut self, program_counter: usize) -> Result<usize, &'static str> {
-        let (id, proc) = self
-            .procs
-            .iter_mut()
-            .enumerate()
-            .find(|(_, p)| p.is_none())
-            .ok_or("Max processes reached")?;
-
-        let mut process = Process {
-            pid: id,
-            stack_pointer: 0,
-            stack: [0; STACK_SIZE],
+    pub const fn new() -> ProcessManager {
+        ProcessManager {
+            procs: Vec::new(),
+            run_queue: VecDeque::new(),
+            current: None,
+            idle_stack_pointer: 0,
+        }
+    }
+
+    /// Returns the pid of the running process, `None` for the idle context
+    pub fn current(&self) -> Option<usize> {
+        self.current
+    }
+
+    /// Creates a process with a `stack_size` bytes stack that starts at `program_counter`
+    /// with `argument` in `s0` and adds it to the run queue
+    pub fn new_process(
+        &mut self,
+        program_counter: usize,
+        argument: usize,
+        stack_size: usize,
+    ) -> Result<usize, &'static str> {
+        let stack = Stack::new(stack_size)?;
+        let process = Some(Process::new(program_counter, argument, stack));
+
+        let id = match self.procs.iter().position(|p| p.is_none()) {
+            Some(id) => {
+                self.procs[id] = process;
+                id
+            }
+            None => {
+                self.procs.push(process);
+                self.procs.len() - 1
+            }
         };
 
-        process.stack[STACK_SIZE - 1] = 0; // s11
-        process.stack[STACK_SIZE - 2] = 0; // s10
-        process.stack[STACK_SIZE - 3] = 0; // s9
-        process.stack[STACK_SIZE - 4] = 0; // s8
-        process.stack[STACK_SIZE - 5] = 0; // s7
-        process.stack[STACK_SIZE - 6] = 0; // s6
-        process.stack[STACK_SIZE - 7] = 0; // s5
-        process.stack[STACK_SIZE - 8] = 0; // s4
-        process.stack[STACK_SIZE - 9] = 0; // s3
-        process.stack[STACK_SIZE - 10] = 0; // s2
-        process.stack[STACK_SIZE - 11] = 0; // s1
-        process.stack[STACK_SIZE - 12] = 0; // s0
-        process.stack[STACK_SIZE - 13] = program_counter; // ra
-
-        *proc = Some(process);
+        self.run_queue.push_back(id);
         Ok(id)
     }
+
+    /// Marks the running process as exited, it will not be scheduled again
+    pub fn exit_current(&mut self) {
+        let pid = self.current.expect("idle context cannot exit");
+        self.process_mut(pid).state = ProcessState::Exited;
+    }
+
+    /// Marks the running process as blocked, it is not scheduled again until it is woken
+    pub fn block_current(&mut self) {
+        let pid = self.current.expect("idle context cannot block");
+        self.process_mut(pid).state = ProcessState::Blocked;
+    }
+
+    /// Puts `pid` back in the run queue if it is blocked
+    pub fn wake(&mut self, pid: usize) {
+        if let Some(Some(process)) = self.procs.get_mut(pid)
+            && process.state == ProcessState::Blocked
+        {
+            process.state = ProcessState::Ready;
+            self.run_queue.push_back(pid);
+        }
+    }
+
+    /// Whether a process is waiting in the run queue
+    pub fn has_ready(&self) -> bool {
+        !self.run_queue.is_empty()
+    }
+
+    /// Frees the slot and the stack of `pid` if it has exited.
+    /// Returns `Ok(true)` if the process was reaped, `Ok(false)` if it is still alive.
+    pub fn reap(&mut self, pid: usize) -> Result<bool, &'static str> {
+        let slot = self.procs.get_mut(pid).ok_or("Invalid pid")?;
+
+        match slot {
+            None => Err("No such process"),
+            Some(Process {
+                state: ProcessState::Exited,
+                ..
+            }) => {
+                *slot = None;
+                Ok(true)
+            }
+            Some(_) => Ok(false),
+        }
+    }
+
+    /// Round-robin: rotates the run queue and returns the stack pointer slots
+    /// to pass to `switch_context`, or `None` if the running context should keep running.
+    ///
+    /// The stacks of both processes are checked for overflows.
+    pub fn switch_next(&mut self) -> Option<(*mut usize, *const usize)> {
+        let prev = self.current;
+
+        match prev {
+            Some(pid) => {
+                let nothing_ready = self.run_queue.is_empty();
+                let process = self.process_mut(pid);
+                process.check_stack(pid);
+
+                if process.state == ProcessState::Running {
+                    if nothing_ready {
+                        return None;
+                    }
+                    process.state = ProcessState::Ready;
+                    self.run_queue.push_back(pid);
+                }
+            }
+            // The idle context is only left when a process is ready
+            None if self.run_queue.is_empty() => return None,
+            None => {}
+        }
+
+        // Falls back to the idle context if the previous process exited or blocked and nothing is
+        // ready
+        let next = self.run_queue.pop_front();
+        self.current = next;
+
+        let prev_sp = match prev {
+            Some(pid) => &mut self.process_mut(pid).stack_pointer as *mut usize,
+            None => &mut self.idle_stack_pointer as *mut usize,
+        };
+        let next_sp = match next {
+            Some(pid) => {
+                let process = self.process_mut(pid);
+                process.check_stack(pid);
+                process.state = ProcessState::Running;
+                &process.stack_pointer as *const usize
+            }
+            None => &self.idle_stack_pointer as *const usize,
+        };
+
+        Some((prev_sp, next_sp))
+    }
+
+    fn process_mut(&mut self, pid: usize) -> &mut Process {
+        self.procs[pid]
+            .as_mut()
+            .expect("scheduled process does not exist")
+    }
+}
+
+impl Default for ProcessManager {
+    fn default() -> Self {
+        Self::new()
+    }
 }
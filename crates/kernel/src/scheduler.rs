@@ -0,0 +1,145 @@
+use alloc::boxed::Box;
+use riscv::interrupt::supervisor as interrupt;
+use spin::Mutex;
+
+use crate::{
+    process::{DEFAULT_STACK_SIZE, ProcessManager},
+    switch_context, thread_entry,
+    time::{self, Duration, Instant},
+};
+
+const TIME_SLICE: Duration = Duration::from_millis(10);
+
+type EntryFn = Box<dyn FnOnce() + Send + 'static>;
+
+// NOTE: The lock must only be taken with interrupts disabled, otherwise the timer
+// interrupt could try to schedule while the interrupted code holds it.
+static PROCESS_MANAGER: Mutex<ProcessManager> = Mutex::new(ProcessManager::new());
+
+/// Enables preemption, the running process is switched out every `TIME_SLICE`
+pub fn start_preemption() {
+    time::set_interval(TIME_SLICE, schedule);
+}
+
+/// Spawns a kernel thread running `f` with the default stack size, returns its pid
+pub fn spawn<F>(f: F) -> Result<usize, &'static str>
+where
+    F: FnOnce() + Send + 'static,
+{
+    spawn_with_stack_size(DEFAULT_STACK_SIZE, f)
+}
+
+/// Spawns a kernel thread running `f` on a `stack_size` bytes stack, returns its pid
+pub fn spawn_with_stack_size<F>(stack_size: usize, f: F) -> Result<usize, &'static str>
+where
+    F: FnOnce() + Send + 'static,
+{
+    let entry: Box<EntryFn> = Box::new(Box::new(f));
+    let argument = Box::into_raw(entry);
+
+    let result = interrupt::free(|| {
+        PROCESS_MANAGER.lock().new_process(
+            thread_entry as *const () as usize,
+            argument as usize,
+            stack_size,
+        )
+    });
+
+    if result.is_err() {
+        // The thread will never run, take ownership back to free the closure
+        drop(unsafe { Box::from_raw(argument) });
+    }
+
+    result
+}
+
+/// Gives the CPU to the next ready process, returns immediately if there is none
+pub fn yield_now() {
+    schedule();
+}
+
+/// Blocks the running thread until `duration` has elapsed, the other processes run meanwhile
+pub fn sleep(duration: Duration) {
+    let deadline = Instant::now().saturating_add(duration);
+
+    let Some(pid) = interrupt::free(|| PROCESS_MANAGER.lock().current()) else {
+        // NOTE: The idle context cannot block, it runs when nothing else is ready. It waits for
+        // interrupts instead, the timer raises one at the deadline.
+        time::wake_at(deadline);
+        while Instant::now() < deadline {
+            yield_now();
+            interrupt::free(|| {
+                // A process woken after this check raises an interrupt that ends `wfi`
+                if !PROCESS_MANAGER.lock().has_ready() {
+                    riscv::asm::wfi();
+                }
+            });
+        }
+        return;
+    };
+
+    // Interrupts stay disabled until the thread is switched out, the timer cannot wake it
+    // before it is blocked
+    interrupt::free(|| {
+        PROCESS_MANAGER.lock().block_current();
+        time::set_timeout(duration, move || wake(pid));
+        schedule();
+    });
+}
+
+/// Terminates the running thread, its slot is freed once it is joined
+pub fn exit() -> ! {
+    interrupt::disable();
+    PROCESS_MANAGER.lock().exit_current();
+    schedule();
+
+    unreachable!("exited thread was scheduled again");
+}
+
+/// Waits for `pid` to exit and frees its slot
+pub fn join(pid: usize) -> Result<(), &'static str> {
+    loop {
+        let reaped = interrupt::free(|| {
+            let mut manager = PROCESS_MANAGER.lock();
+            if manager.current() == Some(pid) {
+                return Err("A thread cannot join itself");
+            }
+            manager.reap(pid)
+        })?;
+
+        if reaped {
+            return Ok(());
+        }
+
+        yield_now();
+    }
+}
+
+fn schedule() {
+    interrupt::free(|| {
+        let Some((prev_sp, next_sp)) = PROCESS_MANAGER.lock().switch_next() else {
+            return;
+        };
+
+        // The lock is released before switching: the next context will take it again.
+        // Interrupts stay disabled, so the pointers cannot be invalidated in between.
+        unsafe { switch_context(prev_sp, next_sp) };
+    });
+}
+
+/// Makes `pid` ready again, called from the timer interrupt
+fn wake(pid: usize) {
+    PROCESS_MANAGER.lock().wake(pid);
+}
+
+/// First Rust code executed by a new thread (jumped to from `thread_entry` in `switch.s`)
+#[unsafe(no_mangle)]
+extern "C" fn thread_start(entry: *mut EntryFn) -> ! {
+    // New threads are switched to with interrupts disabled
+    unsafe { interrupt::enable() };
+
+    let entry = unsafe { Box::from_raw(entry) };
+    entry();
+
+    exit()
+}
@@ -0,0 +1,111 @@
+use core::ops::{Add, AddAssign, Sub, SubAssign};
+
+use core::sync::atomic::Ordering;
+
+use super::{BOOT_TICKS, Duration, duration_to_ticks, ticks, ticks_to_duration};
+
+/// Point in time, counted in ticks of the `time` CSR.
+///
+/// Monotonic: the 64 bits counter does not wrap before centuries at the frequencies of real
+/// hardware.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Instant(u64);
+
+impl Instant {
+    /// Latest representable instant, a deadline that never expires
+    pub const MAX: Instant = Instant(u64::MAX);
+
+    pub fn now() -> Instant {
+        Instant(ticks())
+    }
+
+    /// When the timer was initialized, the origin of `time::uptime`
+    pub fn boot() -> Instant {
+        Instant(BOOT_TICKS.load(Ordering::Relaxed))
+    }
+
+    pub const fn from_ticks(ticks: u64) -> Instant {
+        Instant(ticks)
+    }
+
+    pub const fn ticks(&self) -> u64 {
+        self.0
+    }
+
+    /// Time elapsed from `earlier` to this instant, zero if `earlier` is later
+    pub fn duration_since(&self, earlier: Instant) -> Duration {
+        self.checked_duration_since(earlier).unwrap_or_default()
+    }
+
+    /// `None` if `earlier` is later than this instant
+    pub fn checked_duration_since(&self, earlier: Instant) -> Option<Duration> {
+        self.0.checked_sub(earlier.0).map(ticks_to_duration)
+    }
+
+    /// Time elapsed since this instant
+    pub fn elapsed(&self) -> Duration {
+        Instant::now().duration_since(*self)
+    }
+
+    pub fn checked_add(&self, duration: Duration) -> Option<Instant> {
+        self.0
+            .checked_add(duration_to_ticks(duration)?)
+            .map(Instant)
+    }
+
+    pub fn checked_sub(&self, duration: Duration) -> Option<Instant> {
+        self.0
+            .checked_sub(duration_to_ticks(duration)?)
+            .map(Instant)
+    }
+
+    /// `Instant::MAX` on overflow, for deadlines
+    pub fn saturating_add(&self, duration: Duration) -> Instant {
+        self.checked_add(duration).unwrap_or(Instant::MAX)
+    }
+}
+
+impl Add<Duration> for Instant {
+    type Output = Instant;
+
+    /// # Panics
+    ///
+    /// Panics on overflow, see `checked_add` and `saturating_add`.
+    fn add(self, duration: Duration) -> Instant {
+        self.checked_add(duration)
+            .expect("overflow when adding duration to instant")
+    }
+}
+
+impl AddAssign<Duration> for Instant {
+    fn add_assign(&mut self, duration: Duration) {
+        *self = *self + duration;
+    }
+}
+
+impl Sub<Duration> for Instant {
+    type Output = Instant;
+
+    /// # Panics
+    ///
+    /// Panics when the result would be before tick 0, see `checked_sub`.
+    fn sub(self, duration: Duration) -> Instant {
+        self.checked_sub(duration)
+            .expect("overflow when subtracting duration from instant")
+    }
+}
+
+impl SubAssign<Duration> for Instant {
+    fn sub_assign(&mut self, duration: Duration) {
+        *self = *self - duration;
+    }
+}
+
+impl Sub<Instant> for Instant {
+    type Output = Duration;
+
+    /// Saturates to zero, like `duration_since`
+    fn sub(self, earlier: Instant) -> Duration {
+        self.duration_since(earlier)
+    }
+}
@@ -0,0 +1,98 @@
+mod instant;
+mod system_time;
+mod timer;
+
+use core::arch::asm;
+use core::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+use dtb_reader::DtbReader;
+
+pub use core::time::Duration;
+pub use instant::Instant;
+pub use system_time::{SystemTime, sync_with_rtc};
+pub use timer::{cancel, set_interval, set_timeout, wake_at};
+
+// Frequency of QEMU virt, used when the DTB does not give one
+const DEFAULT_TICKS_PER_SECOND: u64 = 10_000_000;
+const NANOS_PER_SECOND: u64 = 1_000_000_000;
+
+// `stimecmp` CSR of the Sstc extension
+const STIMECMP: usize = 0x14d;
+
+static TICKS_PER_SECOND: AtomicU64 = AtomicU64::new(DEFAULT_TICKS_PER_SECOND);
+// Value of `time` when `init` ran, the firmware does not reset it before starting the kernel
+static BOOT_TICKS: AtomicU64 = AtomicU64::new(0);
+// Whether the timer is programmed with `stimecmp` instead of an SBI call
+static USE_STIMECMP: AtomicBool = AtomicBool::new(false);
+
+/// Reads the frequency of the `time` CSR from `timebase-frequency` and picks the hardware timer
+/// of `hart_id`, then takes over the timer interrupt for the software timers
+pub fn init(dtb: &DtbReader, hart_id: usize) {
+    BOOT_TICKS.store(ticks(), Ordering::Relaxed);
+
+    if let Some(frequency) = dtb.timebase_frequency().filter(|&f| f != 0) {
+        TICKS_PER_SECOND.store(frequency, Ordering::Relaxed);
+    }
+
+    // NOTE: The firmware enables `stimecmp` in `menvcfg` when the hart has Sstc
+    let sstc = dtb
+        .cpu_by_hart_id(hart_id as u64)
+        .is_some_and(|cpu| cpu.has_extension("sstc"));
+    USE_STIMECMP.store(sstc, Ordering::Relaxed);
+
+    timer::init();
+}
+
+pub fn ticks_per_second() -> u64 {
+    TICKS_PER_SECOND.load(Ordering::Relaxed)
+}
+
+/// Whether the hardware timer is programmed with `stimecmp` (Sstc) rather than SBI
+pub fn uses_stimecmp() -> bool {
+    USE_STIMECMP.load(Ordering::Relaxed)
+}
+
+/// Value of the `time` CSR
+pub fn ticks() -> u64 {
+    riscv::register::time::read64()
+}
+
+/// Time since `init`
+pub fn uptime() -> Duration {
+    Instant::now().duration_since(Instant::boot())
+}
+
+/// Duration of `ticks`, rounded down to the nanosecond. Cannot overflow.
+pub fn ticks_to_duration(ticks: u64) -> Duration {
+    let frequency = ticks_per_second();
+    let secs = ticks / frequency;
+    // `rem * NANOS_PER_SECOND` does not fit in 64 bits for frequencies above 18 GHz
+    let rem = (ticks % frequency) as u128;
+    let nanos = rem * NANOS_PER_SECOND as u128 / frequency as u128;
+
+    Duration::new(secs, nanos as u32)
+}
+
+/// Number of ticks in `duration`, rounded up so a deadline is never early. `None` when it does
+/// not fit in 64 bits.
+pub fn duration_to_ticks(duration: Duration) -> Option<u64> {
+    let frequency = ticks_per_second();
+    let secs = duration.as_secs().checked_mul(frequency)?;
+    // Less than `frequency`, so it fits
+    let nanos = (duration.subsec_nanos() as u128 * frequency as u128)
+        .div_ceil(NANOS_PER_SECOND as u128) as u64;
+
+    secs.checked_add(nanos)
+}
+
+/// Raises the timer interrupt once `time` reaches `deadline` and clears the pending one.
+/// `u64::MAX` disables it.
+fn set_hardware_timer(deadline: Instant) {
+    if uses_stimecmp() {
+        unsafe {
+            asm!("csrw {csr}, {value}", csr = const STIMECMP, value = in(reg) deadline.ticks())
+        };
+    } else {
+        sbi::timer::set_timer(deadline.ticks()).expect("failed to set timer");
+    }
+}
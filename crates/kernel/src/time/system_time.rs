@@ -0,0 +1,43 @@
+use core::fmt::{self, Display};
+
+use drivers::{DateTime, RtcDriver, UnixTime};
+use spin::Once;
+
+use super::{Duration, uptime};
+
+// UNIX time at boot, from the RTC
+static BOOT_TIME: Once<Duration> = Once::new();
+
+/// Reads the wall-clock time from `rtc`, `SystemTime::now` then follows the monotonic clock.
+/// Only the first call has an effect.
+pub fn sync_with_rtc(rtc: &mut dyn RtcDriver) {
+    BOOT_TIME.call_once(|| rtc.read_time().since_epoch().saturating_sub(uptime()));
+}
+
+/// Wall-clock time, UNIX time in UTC
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SystemTime(UnixTime);
+
+impl SystemTime {
+    /// Boot time read from the RTC plus the uptime. Counts from the UNIX epoch when the clock
+    /// was never synced with an RTC.
+    pub fn now() -> SystemTime {
+        let boot_time = BOOT_TIME.get().copied().unwrap_or_default();
+        SystemTime(UnixTime::from_duration(boot_time.saturating_add(uptime())))
+    }
+
+    pub fn unix_time(self) -> UnixTime {
+        self.0
+    }
+
+    pub fn to_utc(self) -> DateTime {
+        self.0.to_utc()
+    }
+}
+
+/// ISO 8601, see `DateTime`
+impl Display for SystemTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Display::fmt(&self.to_utc(), f)
+    }
+}
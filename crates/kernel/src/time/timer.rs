@@ -0,0 +1,220 @@
+use alloc::{boxed::Box, collections::BinaryHeap, sync::Arc};
+use core::cmp::Ordering;
+
+use riscv::interrupt::supervisor as interrupt;
+use spin::Mutex;
+
+use super::{Duration, Instant, duration_to_ticks, set_hardware_timer};
+use crate::interrupts::{self, Interrupt, TrapFrame};
+
+// NOTE: The lock must only be taken with interrupts disabled, otherwise the timer
+// interrupt could try to take it while the interrupted code holds it.
+static TIMERS: Mutex<TimerQueue> = Mutex::new(TimerQueue::new());
+
+/// Identifies a timer to cancel it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TimerId(u64);
+
+enum Callback {
+    Once(Box<dyn FnOnce() + Send>),
+    /// Shared with the timer of the next period, a callback that switched to another process
+    /// may still be running when it expires
+    Periodic {
+        period: u64,
+        callback: Arc<dyn Fn() + Send + Sync>,
+    },
+}
+
+impl Callback {
+    fn run(self) {
+        match self {
+            Callback::Once(callback) => callback(),
+            Callback::Periodic { callback, .. } => callback(),
+        }
+    }
+}
+
+struct Timer {
+    deadline: Instant,
+    id: TimerId,
+    callback: Callback,
+}
+
+// `BinaryHeap` is a max-heap: the earliest deadline must be the greatest, timers with the same
+// deadline expire in creation order
+impl Ord for Timer {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (other.deadline, other.id).cmp(&(self.deadline, self.id))
+    }
+}
+
+impl PartialOrd for Timer {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Timer {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Timer {}
+
+/// Software timers, the hardware timer is always armed for the earliest one
+struct TimerQueue {
+    timers: BinaryHeap<Timer>,
+    next_id: u64,
+    /// Deadline without a callback, only there to raise the interrupt
+    wakeup: Option<Instant>,
+}
+
+impl TimerQueue {
+    const fn new() -> TimerQueue {
+        TimerQueue {
+            timers: BinaryHeap::new(),
+            next_id: 0,
+            wakeup: None,
+        }
+    }
+
+    fn add(&mut self, deadline: Instant, callback: Callback) -> TimerId {
+        let id = TimerId(self.next_id);
+        self.next_id += 1;
+
+        self.timers.push(Timer {
+            deadline,
+            id,
+            callback,
+        });
+        self.arm();
+
+        id
+    }
+
+    fn cancel(&mut self, id: TimerId) -> bool {
+        let len = self.timers.len();
+        self.timers.retain(|timer| timer.id != id);
+        self.arm();
+
+        self.timers.len() != len
+    }
+
+    /// Replaces the wakeup deadline, the earlier one is dropped
+    fn wake_at(&mut self, deadline: Instant) {
+        self.wakeup = Some(deadline);
+        self.arm();
+    }
+
+    /// Removes the earliest timer expired at `now` and returns its callback. A periodic timer
+    /// is added back for its next period, periods already missed are skipped.
+    fn pop_expired(&mut self, now: Instant) -> Option<Callback> {
+        if self.wakeup.is_some_and(|wakeup| wakeup <= now) {
+            self.wakeup = None;
+        }
+
+        let expired = match self.timers.peek() {
+            Some(timer) if timer.deadline <= now => self.timers.pop(),
+            _ => None,
+        };
+
+        let callback = expired.map(|timer| match timer.callback {
+            Callback::Once(_) => timer.callback,
+            Callback::Periodic { period, callback } => {
+                let missed = (now.ticks() - timer.deadline.ticks()) / period;
+                let next = timer
+                    .deadline
+                    .ticks()
+                    .saturating_add((missed + 1).saturating_mul(period));
+
+                self.timers.push(Timer {
+                    deadline: Instant::from_ticks(next),
+                    id: timer.id,
+                    callback: Callback::Periodic {
+                        period,
+                        callback: callback.clone(),
+                    },
+                });
+                Callback::Periodic { period, callback }
+            }
+        });
+
+        self.arm();
+        callback
+    }
+
+    /// Programs the hardware timer for the earliest deadline
+    fn arm(&self) {
+        let deadline = self
+            .timers
+            .peek()
+            .map_or(Instant::MAX, |timer| timer.deadline)
+            .min(self.wakeup.unwrap_or(Instant::MAX));
+        set_hardware_timer(deadline);
+    }
+}
+
+/// Calls `callback` once, from the timer interrupt, when `delay` has elapsed.
+///
+/// Callbacks run with interrupts disabled: they must be short and must not take a lock that the
+/// interrupted code may hold.
+pub fn set_timeout<F>(delay: Duration, callback: F) -> TimerId
+where
+    F: FnOnce() + Send + 'static,
+{
+    let deadline = Instant::now().saturating_add(delay);
+    interrupt::free(|| {
+        TIMERS
+            .lock()
+            .add(deadline, Callback::Once(Box::new(callback)))
+    })
+}
+
+/// Calls `callback` every `period` from the timer interrupt, until the timer is cancelled.
+///
+/// Same constraints as `set_timeout`. The callback may switch to another process, the other
+/// expired timers then run on the next timer interrupt.
+pub fn set_interval<F>(period: Duration, callback: F) -> TimerId
+where
+    F: Fn() + Send + Sync + 'static,
+{
+    let period = duration_to_ticks(period).unwrap_or(u64::MAX).max(1);
+    let deadline = Instant::from_ticks(Instant::now().ticks().saturating_add(period));
+
+    let callback = Callback::Periodic {
+        period,
+        callback: Arc::new(callback),
+    };
+    interrupt::free(|| TIMERS.lock().add(deadline, callback))
+}
+
+/// Stops a timer, `false` if it already expired or was cancelled. A callback that is already
+/// running is not stopped.
+pub fn cancel(id: TimerId) -> bool {
+    interrupt::free(|| TIMERS.lock().cancel(id))
+}
+
+/// Raises the timer interrupt at `deadline` without running anything, to end a `wfi`.
+///
+/// There is a single wakeup: it replaces the previous one instead of adding a timer.
+pub fn wake_at(deadline: Instant) {
+    interrupt::free(|| TIMERS.lock().wake_at(deadline));
+}
+
+pub(super) fn init() {
+    // Nothing is pending until the first timer is added
+    interrupt::free(|| TIMERS.lock().arm());
+    interrupts::register_handler(Interrupt::SupervisorTimer, on_timer_interrupt);
+}
+
+fn on_timer_interrupt(_frame: &mut TrapFrame) {
+    // The lock is released before each callback: it may add timers or switch to another process
+    loop {
+        let expired = TIMERS.lock().pop_expired(Instant::now());
+        let Some(callback) = expired else {
+            break;
+        };
+        callback.run();
+    }
+}
@@ -1,16 +1,24 @@
-use alloc::format;
+use alloc::string::String;
+use core::fmt::Write;
 
-use crate::{LOGGERS, LogLevel};
+use crate::{LOGGERS, LogLevel, TIMESTAMP};
 
 /// # Warning: Do not call directly!
 ///
 /// Use the macros instead: `error!()`, `warn!()`, `info!()`, `debug!()`
 pub fn log<S: AsRef<str>>(level: LogLevel, message: S) {
     if let Some(loggers) = LOGGERS.get() {
-        let message = format!("[{}] {}\n", level.as_str(), message.as_ref());
+        let mut line = String::new();
+        // NOTE: Writing to a `String` cannot fail
+        if let Some(timestamp) = TIMESTAMP.get() {
+            let _ = timestamp(&mut line);
+            line.push(' ');
+        }
+        let _ = writeln!(line, "[{}] {}", level.as_str(), message.as_ref());
+
         for logger in &mut *loggers.lock() {
             // TODO: Don't panic on write failure
-            logger.lock().write_str(message.as_ref()).unwrap();
+            logger.lock().write_str(&line).unwrap();
         }
     }
 }
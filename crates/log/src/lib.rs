@@ -3,16 +3,21 @@
 extern crate alloc;
 
 use alloc::{sync::Arc, vec::Vec};
-use core::fmt::Write;
+use core::fmt::{self, Write};
 use spin::{Mutex, Once};
 
 pub use crate::level::LogLevel;
+pub use crate::writer::LogWriter;
 pub mod internal;
 pub mod level;
+mod writer;
 
 type Logger = Arc<Mutex<dyn Write + Send + Sync>>;
 static LOGGERS: Once<Mutex<Vec<Logger>>> = Once::new();
 
+type Timestamp = fn(&mut dyn Write) -> fmt::Result;
+static TIMESTAMP: Once<Timestamp> = Once::new();
+
 /// Add logger to the global list
 pub fn add_logger(logger: Arc<Mutex<dyn Write + Send + Sync>>) {
     LOGGERS
@@ -21,6 +26,11 @@ pub fn add_logger(logger: Arc<Mutex<dyn Write + Send + Sync>>) {
         .push(logger);
 }
 
+/// Prefix every line with the output of `timestamp`, can only be set once
+pub fn set_timestamp(timestamp: Timestamp) {
+    TIMESTAMP.call_once(|| timestamp);
+}
+
 const fn parse_log_level() -> u8 {
     match option_env!("LOG_LEVEL") {
         Some(level_str) => {
@@ -85,4 +95,3 @@ macro_rules! debug {
         }
     }};
 }
-
@@ -0,0 +1,54 @@
+use alloc::string::String;
+use core::fmt::{self, Write};
+
+use crate::{LOG_LEVEL, LogLevel, internal};
+
+/// Logs every line written to it at `level`, for output made of many lines like a DTS dump.
+/// An unfinished last line is logged when the writer is dropped.
+pub struct LogWriter {
+    level: LogLevel,
+    line: String,
+}
+
+impl LogWriter {
+    pub fn new(level: LogLevel) -> LogWriter {
+        LogWriter {
+            level,
+            line: String::new(),
+        }
+    }
+
+    //
+    // NON-PUBLIC INTERFACE
+    //
+
+    fn flush_line(&mut self) {
+        if self.level.as_u8() <= LOG_LEVEL {
+            internal::log(self.level, &self.line);
+        }
+        self.line.clear();
+    }
+}
+
+impl Write for LogWriter {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let mut lines = s.split('\n');
+
+        // The first part continues the current line, every other one starts a new line
+        self.line.push_str(lines.next().unwrap_or_default());
+        for line in lines {
+            self.flush_line();
+            self.line.push_str(line);
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for LogWriter {
+    fn drop(&mut self) {
+        if !self.line.is_empty() {
+            self.flush_line();
+        }
+    }
+}
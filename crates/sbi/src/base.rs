@@ -0,0 +1,85 @@
+use crate::{Extension, SbiResult, call::sbi_call};
+
+const GET_SPEC_VERSION: usize = 0;
+const GET_IMPL_ID: usize = 1;
+const GET_IMPL_VERSION: usize = 2;
+const PROBE_EXTENSION: usize = 3;
+const GET_MVENDORID: usize = 4;
+const GET_MARCHID: usize = 5;
+const GET_MIMPID: usize = 6;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpecVersion {
+    pub major: usize,
+    pub minor: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImplementationId {
+    BerkeleyBootLoader,
+    OpenSbi,
+    Xvisor,
+    Kvm,
+    RustSbi,
+    Diosix,
+    Coffer,
+    Xen,
+    PolarFireHss,
+    Unknown(usize),
+}
+
+impl From<usize> for ImplementationId {
+    fn from(id: usize) -> Self {
+        match id {
+            0 => Self::BerkeleyBootLoader,
+            1 => Self::OpenSbi,
+            2 => Self::Xvisor,
+            3 => Self::Kvm,
+            4 => Self::RustSbi,
+            5 => Self::Diosix,
+            6 => Self::Coffer,
+            7 => Self::Xen,
+            8 => Self::PolarFireHss,
+            _ => Self::Unknown(id),
+        }
+    }
+}
+
+fn base_call(function: usize, arg0: usize) -> SbiResult<usize> {
+    sbi_call(Extension::Base.eid(), function, [arg0, 0, 0, 0, 0, 0])
+}
+
+pub fn get_spec_version() -> SbiResult<SpecVersion> {
+    let version = base_call(GET_SPEC_VERSION, 0)?;
+    Ok(SpecVersion {
+        major: (version >> 24) & 0x7f,
+        minor: version & 0xff_ffff,
+    })
+}
+
+pub fn get_impl_id() -> SbiResult<ImplementationId> {
+    base_call(GET_IMPL_ID, 0).map(ImplementationId::from)
+}
+
+pub fn get_impl_version() -> SbiResult<usize> {
+    base_call(GET_IMPL_VERSION, 0)
+}
+
+/// Returns `0` if the extension is not available, an extension specific non-zero value otherwise.
+///
+/// Prefer [`crate::is_available`] which caches the result.
+pub fn probe_extension(extension_id: usize) -> SbiResult<usize> {
+    base_call(PROBE_EXTENSION, extension_id)
+}
+
+pub fn get_mvendorid() -> SbiResult<usize> {
+    base_call(GET_MVENDORID, 0)
+}
+
+pub fn get_marchid() -> SbiResult<usize> {
+    base_call(GET_MARCHID, 0)
+}
+
+pub fn get_mimpid() -> SbiResult<usize> {
+    base_call(GET_MIMPID, 0)
+}
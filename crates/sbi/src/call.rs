@@ -0,0 +1,48 @@
+use core::arch::asm;
+
+use crate::{SbiError, SbiResult};
+
+/// Performs an SBI call, returns `sbiret.value` on success.
+///
+/// Calling convention: `a7` = extension ID, `a6` = function ID, `a0`-`a5` = arguments.
+/// The SBI returns the error code in `a0` and the value in `a1`.
+#[inline(always)]
+pub(crate) fn sbi_call(extension: usize, function: usize, args: [usize; 6]) -> SbiResult<usize> {
+    let error: isize;
+    let value: usize;
+
+    unsafe {
+        asm!(
+            "ecall",
+            inlateout("a0") args[0] => error,
+            inlateout("a1") args[1] => value,
+            in("a2") args[2],
+            in("a3") args[3],
+            in("a4") args[4],
+            in("a5") args[5],
+            in("a6") function,
+            in("a7") extension,
+        );
+    }
+
+    match error {
+        0 => Ok(value),
+        e => Err(SbiError::from(e)),
+    }
+}
+
+/// Performs a legacy (SBI v0.1) call, they only return a value in `a0`
+#[inline(always)]
+pub(crate) fn legacy_call(extension: usize, arg0: usize) -> isize {
+    let ret: isize;
+
+    unsafe {
+        asm!(
+            "ecall",
+            inlateout("a0") arg0 => ret,
+            in("a7") extension,
+        );
+    }
+
+    ret
+}
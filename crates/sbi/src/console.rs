@@ -0,0 +1,68 @@
+use crate::{
+    Extension, SbiResult,
+    call::{legacy_call, sbi_call},
+};
+
+const CONSOLE_WRITE: usize = 0;
+const CONSOLE_READ: usize = 1;
+const CONSOLE_WRITE_BYTE: usize = 2;
+
+const LEGACY_CONSOLE_PUTCHAR: usize = 0x01;
+const LEGACY_CONSOLE_GETCHAR: usize = 0x02;
+
+// NOTE: The DBCN extension takes physical addresses, buffers are expected to be identity mapped
+
+/// Writes `bytes` to the debug console, returns the number of bytes written.
+///
+/// Falls back to the legacy call (one byte at a time) on firmwares without the DBCN extension.
+pub fn write(bytes: &[u8]) -> SbiResult<usize> {
+    if crate::is_available(Extension::DebugConsole) {
+        sbi_call(
+            Extension::DebugConsole.eid(),
+            CONSOLE_WRITE,
+            [bytes.len(), bytes.as_ptr() as usize, 0, 0, 0, 0],
+        )
+    } else {
+        for &byte in bytes {
+            legacy_call(LEGACY_CONSOLE_PUTCHAR, byte as usize);
+        }
+        Ok(bytes.len())
+    }
+}
+
+/// Reads up to `buffer.len()` bytes without blocking, returns the number of bytes read.
+///
+/// Falls back to the legacy call on firmwares without the DBCN extension.
+pub fn read(buffer: &mut [u8]) -> SbiResult<usize> {
+    if crate::is_available(Extension::DebugConsole) {
+        sbi_call(
+            Extension::DebugConsole.eid(),
+            CONSOLE_READ,
+            [buffer.len(), buffer.as_mut_ptr() as usize, 0, 0, 0, 0],
+        )
+    } else {
+        let mut count = 0;
+        for byte in buffer.iter_mut() {
+            match legacy_call(LEGACY_CONSOLE_GETCHAR, 0) {
+                c if c < 0 => break,
+                c => *byte = c as u8,
+            }
+            count += 1;
+        }
+        Ok(count)
+    }
+}
+
+pub fn write_byte(byte: u8) -> SbiResult<()> {
+    if crate::is_available(Extension::DebugConsole) {
+        sbi_call(
+            Extension::DebugConsole.eid(),
+            CONSOLE_WRITE_BYTE,
+            [byte as usize, 0, 0, 0, 0, 0],
+        )?;
+    } else {
+        legacy_call(LEGACY_CONSOLE_PUTCHAR, byte as usize);
+    }
+
+    Ok(())
+}
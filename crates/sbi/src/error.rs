@@ -0,0 +1,43 @@
+/// Standard SBI error codes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SbiError {
+    Failed,
+    NotSupported,
+    InvalidParam,
+    Denied,
+    InvalidAddress,
+    AlreadyAvailable,
+    AlreadyStarted,
+    AlreadyStopped,
+    NoSharedMemory,
+    InvalidState,
+    BadRange,
+    Timeout,
+    Io,
+    DeniedLocked,
+    Unknown(isize),
+}
+
+pub type SbiResult<T> = Result<T, SbiError>;
+
+impl From<isize> for SbiError {
+    fn from(code: isize) -> Self {
+        match code {
+            -1 => Self::Failed,
+            -2 => Self::NotSupported,
+            -3 => Self::InvalidParam,
+            -4 => Self::Denied,
+            -5 => Self::InvalidAddress,
+            -6 => Self::AlreadyAvailable,
+            -7 => Self::AlreadyStarted,
+            -8 => Self::AlreadyStopped,
+            -9 => Self::NoSharedMemory,
+            -10 => Self::InvalidState,
+            -11 => Self::BadRange,
+            -12 => Self::Timeout,
+            -13 => Self::Io,
+            -14 => Self::DeniedLocked,
+            _ => Self::Unknown(code),
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use crate::{Extension, SbiError, SbiResult, call::sbi_call};
+
+const HART_START: usize = 0;
+const HART_STOP: usize = 1;
+const HART_GET_STATUS: usize = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HartState {
+    Started,
+    Stopped,
+    StartPending,
+    StopPending,
+    Suspended,
+    SuspendPending,
+    ResumePending,
+}
+
+impl TryFrom<usize> for HartState {
+    type Error = SbiError;
+
+    fn try_from(value: usize) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::Started),
+            1 => Ok(Self::Stopped),
+            2 => Ok(Self::StartPending),
+            3 => Ok(Self::StopPending),
+            4 => Ok(Self::Suspended),
+            5 => Ok(Self::SuspendPending),
+            6 => Ok(Self::ResumePending),
+            _ => Err(SbiError::Unknown(value as isize)),
+        }
+    }
+}
+
+fn hsm_call(function: usize, args: [usize; 3]) -> SbiResult<usize> {
+    crate::require(Extension::Hsm)?;
+
+    sbi_call(
+        Extension::Hsm.eid(),
+        function,
+        [args[0], args[1], args[2], 0, 0, 0],
+    )
+}
+
+/// Starts `hart_id` in supervisor mode at the physical address `start_addr`.
+///
+/// The hart begins with `a0 = hart_id`, `a1 = opaque` and the MMU and interrupts disabled.
+pub fn hart_start(hart_id: usize, start_addr: usize, opaque: usize) -> SbiResult<()> {
+    hsm_call(HART_START, [hart_id, start_addr, opaque])?;
+    Ok(())
+}
+
+/// Stops the calling hart, only returns on failure
+pub fn hart_stop() -> SbiError {
+    match hsm_call(HART_STOP, [0, 0, 0]) {
+        Ok(_) => SbiError::Failed,
+        Err(e) => e,
+    }
+}
+
+pub fn hart_get_status(hart_id: usize) -> SbiResult<HartState> {
+    HartState::try_from(hsm_call(HART_GET_STATUS, [hart_id, 0, 0])?)
+}
@@ -0,0 +1,15 @@
+use crate::{Extension, HartMask, SbiResult, call::sbi_call};
+
+const SEND_IPI: usize = 0;
+
+/// Sends a supervisor software interrupt to every hart in `harts`
+pub fn send_ipi(harts: HartMask) -> SbiResult<()> {
+    crate::require(Extension::Ipi)?;
+
+    sbi_call(
+        Extension::Ipi.eid(),
+        SEND_IPI,
+        [harts.mask, harts.base, 0, 0, 0, 0],
+    )?;
+    Ok(())
+}
@@ -0,0 +1,111 @@
+//! Client for the RISC-V Supervisor Binary Interface
+//!
+//! Spec: https://github.com/riscv-non-isa/riscv-sbi-doc
+
+#![no_std]
+
+mod call;
+mod error;
+
+pub mod base;
+pub mod console;
+pub mod hsm;
+pub mod ipi;
+pub mod reset;
+pub mod rfence;
+pub mod timer;
+
+use spin::Once;
+
+pub use error::{SbiError, SbiResult};
+
+/// SBI extensions supported by this crate
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Extension {
+    Base,
+    Timer,
+    Ipi,
+    Rfence,
+    Hsm,
+    Reset,
+    DebugConsole,
+}
+
+impl Extension {
+    const COUNT: usize = 7;
+
+    /// Extension ID (EID) as defined by the spec
+    pub const fn eid(&self) -> usize {
+        match self {
+            Extension::Base => 0x10,
+            Extension::Timer => 0x54494D45,
+            Extension::Ipi => 0x735049,
+            Extension::Rfence => 0x52464E43,
+            Extension::Hsm => 0x48534D,
+            Extension::Reset => 0x53525354,
+            Extension::DebugConsole => 0x4442434E,
+        }
+    }
+
+    const fn index(&self) -> usize {
+        match self {
+            Extension::Base => 0,
+            Extension::Timer => 1,
+            Extension::Ipi => 2,
+            Extension::Rfence => 3,
+            Extension::Hsm => 4,
+            Extension::Reset => 5,
+            Extension::DebugConsole => 6,
+        }
+    }
+}
+
+static PROBED: [Once<bool>; Extension::COUNT] = [const { Once::new() }; Extension::COUNT];
+
+/// Returns `true` if the SBI implementation provides `extension`.
+/// The firmware is only asked once, the result is cached for later calls.
+pub fn is_available(extension: Extension) -> bool {
+    *PROBED[extension.index()].call_once(|| match extension {
+        // Base is mandatory since SBI v0.2, legacy firmwares fail this call
+        Extension::Base => base::get_spec_version().is_ok(),
+        _ => base::probe_extension(extension.eid()).is_ok_and(|v| v != 0),
+    })
+}
+
+/// Returns `Err(SbiError::NotSupported)` if `extension` is missing
+fn require(extension: Extension) -> SbiResult<()> {
+    if is_available(extension) {
+        Ok(())
+    } else {
+        Err(SbiError::NotSupported)
+    }
+}
+
+/// Set of harts targeted by a call, as a window of `usize::BITS` harts starting at `base`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HartMask {
+    mask: usize,
+    base: usize,
+}
+
+impl HartMask {
+    pub const fn new(mask: usize, base: usize) -> HartMask {
+        HartMask { mask, base }
+    }
+
+    /// Targets every hart of the system
+    pub const fn all() -> HartMask {
+        // A base of -1 tells the SBI to ignore the mask
+        HartMask {
+            mask: 0,
+            base: usize::MAX,
+        }
+    }
+
+    pub const fn single(hart_id: usize) -> HartMask {
+        HartMask {
+            mask: 1,
+            base: hart_id,
+        }
+    }
+}
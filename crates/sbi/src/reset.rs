@@ -0,0 +1,61 @@
+use crate::{
+    Extension, SbiError,
+    call::{legacy_call, sbi_call},
+};
+
+const SYSTEM_RESET: usize = 0;
+const LEGACY_SHUTDOWN: usize = 0x08;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResetType {
+    Shutdown,
+    ColdReboot,
+    WarmReboot,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResetReason {
+    NoReason,
+    SystemFailure,
+}
+
+/// Resets the system, only returns on failure
+pub fn system_reset(reset_type: ResetType, reason: ResetReason) -> SbiError {
+    if let Err(e) = crate::require(Extension::Reset) {
+        return e;
+    }
+
+    let reset_type = match reset_type {
+        ResetType::Shutdown => 0,
+        ResetType::ColdReboot => 1,
+        ResetType::WarmReboot => 2,
+    };
+    let reason = match reason {
+        ResetReason::NoReason => 0,
+        ResetReason::SystemFailure => 1,
+    };
+
+    match sbi_call(
+        Extension::Reset.eid(),
+        SYSTEM_RESET,
+        [reset_type, reason, 0, 0, 0, 0],
+    ) {
+        Ok(_) => SbiError::Failed,
+        Err(e) => e,
+    }
+}
+
+/// Powers off the system.
+///
+/// Falls back to the legacy call on firmwares without the SRST extension.
+pub fn shutdown() -> SbiError {
+    let error = system_reset(ResetType::Shutdown, ResetReason::NoReason);
+    if error == SbiError::NotSupported {
+        legacy_call(LEGACY_SHUTDOWN, 0);
+    }
+    error
+}
+
+pub fn reboot() -> SbiError {
+    system_reset(ResetType::ColdReboot, ResetReason::NoReason)
+}
@@ -0,0 +1,37 @@
+use crate::{Extension, HartMask, SbiResult, call::sbi_call};
+
+const REMOTE_FENCE_I: usize = 0;
+const REMOTE_SFENCE_VMA: usize = 1;
+const REMOTE_SFENCE_VMA_ASID: usize = 2;
+
+fn rfence_call(function: usize, harts: HartMask, args: [usize; 3]) -> SbiResult<()> {
+    crate::require(Extension::Rfence)?;
+
+    sbi_call(
+        Extension::Rfence.eid(),
+        function,
+        [harts.mask, harts.base, args[0], args[1], args[2], 0],
+    )?;
+    Ok(())
+}
+
+/// Executes `FENCE.I` on every hart in `harts`
+pub fn remote_fence_i(harts: HartMask) -> SbiResult<()> {
+    rfence_call(REMOTE_FENCE_I, harts, [0, 0, 0])
+}
+
+/// Executes `SFENCE.VMA` for the virtual range `[start, start + size)` on every hart in `harts`.
+/// A `size` of `usize::MAX` flushes the whole address space.
+pub fn remote_sfence_vma(harts: HartMask, start: usize, size: usize) -> SbiResult<()> {
+    rfence_call(REMOTE_SFENCE_VMA, harts, [start, size, 0])
+}
+
+/// Same as [`remote_sfence_vma`] but only for the given `asid`
+pub fn remote_sfence_vma_asid(
+    harts: HartMask,
+    start: usize,
+    size: usize,
+    asid: usize,
+) -> SbiResult<()> {
+    rfence_call(REMOTE_SFENCE_VMA_ASID, harts, [start, size, asid])
+}
@@ -0,0 +1,25 @@
+use crate::{
+    Extension, SbiResult,
+    call::{legacy_call, sbi_call},
+};
+
+const SET_TIMER: usize = 0;
+const LEGACY_SET_TIMER: usize = 0x00;
+
+/// Programs the next timer event at the absolute time `stime_value` (in `time` ticks)
+/// and clears the pending supervisor timer interrupt.
+///
+/// Falls back to the legacy call on firmwares without the TIME extension.
+pub fn set_timer(stime_value: u64) -> SbiResult<()> {
+    if crate::is_available(Extension::Timer) {
+        sbi_call(
+            Extension::Timer.eid(),
+            SET_TIMER,
+            [stime_value as usize, 0, 0, 0, 0, 0],
+        )?;
+    } else {
+        legacy_call(LEGACY_SET_TIMER, stime_value as usize);
+    }
+
+    Ok(())
+}